@@ -109,9 +109,8 @@ fn main() {
         // Draw the texture
         window.render_mesh(&mesh);
 
-        // Unbinds the render texture and sets the default viewport size
+        // Unbinds the render texture, which restores the windows framebuffer and viewport
         render_texture.unbind();
-        window.update_viewport(window.get_window_size().0, window.get_window_size().1);
 
         // Clears the default viewports color and depth buffers
         window.clear_color(Vec4::new(0.1, 0.1, 0.1, 1.0));
@@ -183,8 +183,6 @@ fn main() {
         depth_texture.unbind();
 
         // Main render pass
-        window.update_viewport(window.get_window_size().0, window.get_window_size().1);
-        
         // Draw floor with shadows
         floor_texture.bind(0);
         depth_texture.depth_texture().unwrap().bind(1);
@@ -0,0 +1,244 @@
+extern crate gl;
+
+use std::ops::BitOr;
+
+use gl::types::*;
+
+use crate::{FilterMode, RenderTexture};
+
+/// A rectangle of pixels in a framebuffer, with the origin in the bottom left corner like OpenGL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Rect {
+    /// Creates a new rectangle from its bottom left corner and size.
+    pub fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+        Rect { x, y, width, height }
+    }
+
+    /// Creates a rectangle starting at the origin, covering a whole framebuffer of the given size.
+    pub fn from_size(width: i32, height: i32) -> Self {
+        Rect { x: 0, y: 0, width, height }
+    }
+}
+
+/// The buffers a blit copies, which can be combined with `|`, for example `BlitMask::DEPTH | BlitMask::STENCIL`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlitMask(GLbitfield);
+
+impl BlitMask {
+    /// Copies the color of the read buffer, which is the first color attachment of render textures.
+    pub const COLOR: BlitMask = BlitMask(gl::COLOR_BUFFER_BIT);
+    /// Copies the depth values.
+    pub const DEPTH: BlitMask = BlitMask(gl::DEPTH_BUFFER_BIT);
+    /// Copies the stencil values.
+    pub const STENCIL: BlitMask = BlitMask(gl::STENCIL_BUFFER_BIT);
+    /// Copies color, depth and stencil values.
+    pub const ALL: BlitMask = BlitMask(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT);
+
+    /// Returns if all buffers of `other` are part of this mask.
+    pub fn contains(self, other: BlitMask) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for BlitMask {
+    type Output = BlitMask;
+
+    fn bitor(self, rhs: BlitMask) -> BlitMask {
+        BlitMask(self.0 | rhs.0)
+    }
+}
+
+/// Copies a rectangle of one framebuffer into a rectangle of another with `glBlitFramebuffer`, scaling it if the
+/// rectangles differ in size. `None` stands for the window (the default framebuffer). Depth and stencil values can
+/// only be copied between matching formats and with [`FilterMode::Nearest`].
+///
+/// Multisampled render textures are resolved by the copy itself if both rectangles have the same size, otherwise
+/// they are resolved with [`RenderTexture::resolve`] first and their textures are copied. Copying into a multisampled
+/// render texture or window is only possible from a multisampled render texture with the same amount of samples and
+/// without scaling. Integer color formats can only be copied with [`FilterMode::Nearest`].
+pub fn blit(
+    src: Option<&RenderTexture>,
+    src_rect: Rect,
+    dst: Option<&RenderTexture>,
+    dst_rect: Rect,
+    filter: FilterMode,
+    mask: BlitMask,
+) -> Result<(), String> {
+    if filter == FilterMode::Linear && (mask.contains(BlitMask::DEPTH) || mask.contains(BlitMask::STENCIL)) {
+        return Err("[FerrousGl Error] Depth and stencil values can only be blitted with nearest filtering".to_string());
+    }
+
+    let scaled = (src_rect.width, src_rect.height) != (dst_rect.width, dst_rect.height);
+    let src_samples = match src {
+        Some(src) => src.samples(),
+        None => default_framebuffer_samples(),
+    };
+    let dst_samples = match dst {
+        Some(dst) => dst.samples(),
+        None => default_framebuffer_samples(),
+    };
+
+    if dst_samples > 1 && (src.is_none() || src_samples != dst_samples || scaled) {
+        return Err(format!(
+            "[FerrousGl Error] Can not blit into a framebuffer with {} samples from a framebuffer with {} samples{}",
+            dst_samples,
+            src_samples,
+            if scaled { " with scaling" } else { "" }
+        ));
+    }
+    // Multisampled render textures are resolved before scaling, the window can not be resolved this way
+    if src.is_none() && src_samples > 1 && scaled {
+        return Err("[FerrousGl Error] Can not blit from the multisampled window framebuffer with scaling".to_string());
+    }
+
+    if filter == FilterMode::Linear && mask.contains(BlitMask::COLOR) {
+        let is_integer = |render_texture: Option<&RenderTexture>| {
+            render_texture
+                .and_then(|render_texture| render_texture.color_texture(0))
+                .is_some_and(|texture| texture.format.is_integer())
+        };
+        if is_integer(src) || is_integer(dst) {
+            return Err("[FerrousGl Error] Integer color formats can only be blitted with nearest filtering".to_string());
+        }
+    }
+
+    let read_framebuffer = src.map_or(0, |src| src.read_framebuffer_id(scaled));
+    let draw_framebuffer = dst.map_or(0, |dst| dst.framebuffer_id());
+
+    unsafe {
+        let (mut previous_read_framebuffer, mut previous_draw_framebuffer) = (0, 0);
+        gl::GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut previous_read_framebuffer);
+        gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut previous_draw_framebuffer);
+
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, read_framebuffer);
+        gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, draw_framebuffer);
+
+        gl::BlitFramebuffer(
+            src_rect.x,
+            src_rect.y,
+            src_rect.x + src_rect.width,
+            src_rect.y + src_rect.height,
+            dst_rect.x,
+            dst_rect.y,
+            dst_rect.x + dst_rect.width,
+            dst_rect.y + dst_rect.height,
+            mask.0,
+            match filter {
+                FilterMode::Linear => gl::LINEAR,
+                FilterMode::Nearest => gl::NEAREST,
+            },
+        );
+
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, previous_read_framebuffer as GLuint);
+        gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, previous_draw_framebuffer as GLuint);
+    }
+
+    Ok(())
+}
+
+/// Internal function returning the amount of samples of the window (default framebuffer), 0 if it is not multisampled.
+pub(crate) fn default_framebuffer_samples() -> u32 {
+    unsafe {
+        let mut previous_framebuffer = 0;
+        gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut previous_framebuffer);
+        gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, 0);
+
+        let (mut sample_buffers, mut samples) = (0, 0);
+        gl::GetIntegerv(gl::SAMPLE_BUFFERS, &mut sample_buffers);
+        gl::GetIntegerv(gl::SAMPLES, &mut samples);
+
+        gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, previous_framebuffer as GLuint);
+
+        if sample_buffers > 0 {
+            samples as u32
+        } else {
+            0
+        }
+    }
+}
+
+/// Enum representing the ways a render texture is fitted into a window of a different size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScalingMode {
+    /// Scales the image as large as possible while keeping its aspect ratio, leaving black bars at the sides.
+    Letterbox,
+    /// Stretches the image over the whole window, distorting it if the aspect ratios differ.
+    Stretch,
+    /// Scales the image by the largest whole number that fits into the window and centers it, so every pixel of the
+    /// image covers the same amount of window pixels. Useful for pixel art, falls back to [`ScalingMode::Letterbox`]
+    /// if the image is larger than the window.
+    IntegerScaling,
+}
+
+impl ScalingMode {
+    /// Returns the rectangle an image of the source size is drawn into, in a window of the target size.
+    pub fn fit(self, source_width: i32, source_height: i32, target_width: i32, target_height: i32) -> Rect {
+        if source_width <= 0 || source_height <= 0 {
+            return Rect::from_size(target_width, target_height);
+        }
+
+        let (width, height) = match self {
+            ScalingMode::Stretch => return Rect::from_size(target_width, target_height),
+            ScalingMode::IntegerScaling
+                if source_width <= target_width && source_height <= target_height =>
+            {
+                let factor = (target_width / source_width).min(target_height / source_height);
+                (source_width * factor, source_height * factor)
+            }
+            ScalingMode::Letterbox | ScalingMode::IntegerScaling => {
+                let scale = (target_width as f32 / source_width as f32).min(target_height as f32 / source_height as f32);
+                (
+                    (source_width as f32 * scale).round() as i32,
+                    (source_height as f32 * scale).round() as i32,
+                )
+            }
+        };
+
+        Rect::new((target_width - width) / 2, (target_height - height) / 2, width, height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn letterbox_keeps_aspect_ratio() {
+        // A 4:3 image in a 16:9 window gets bars on the sides
+        assert_eq!(ScalingMode::Letterbox.fit(320, 240, 1920, 1080), Rect::new(240, 0, 1440, 1080));
+        // A 16:9 image in a 4:3 window gets bars at the top and bottom
+        assert_eq!(ScalingMode::Letterbox.fit(1920, 1080, 800, 600), Rect::new(0, 75, 800, 450));
+    }
+
+    #[test]
+    fn stretch_covers_the_window() {
+        assert_eq!(ScalingMode::Stretch.fit(320, 240, 1920, 1080), Rect::from_size(1920, 1080));
+    }
+
+    #[test]
+    fn integer_scaling_uses_whole_factors() {
+        assert_eq!(ScalingMode::IntegerScaling.fit(320, 180, 1920, 1080), Rect::new(0, 0, 1920, 1080));
+        assert_eq!(ScalingMode::IntegerScaling.fit(320, 180, 1280, 800), Rect::new(0, 40, 1280, 720));
+        assert_eq!(ScalingMode::IntegerScaling.fit(320, 240, 1000, 1000), Rect::new(20, 140, 960, 720));
+    }
+
+    #[test]
+    fn integer_scaling_falls_back_to_letterbox_for_large_images() {
+        assert_eq!(
+            ScalingMode::IntegerScaling.fit(1920, 1080, 800, 600),
+            ScalingMode::Letterbox.fit(1920, 1080, 800, 600)
+        );
+    }
+
+    #[test]
+    fn empty_images_cover_the_window() {
+        assert_eq!(ScalingMode::Letterbox.fit(0, 240, 800, 600), Rect::from_size(800, 600));
+    }
+}
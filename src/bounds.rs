@@ -0,0 +1,333 @@
+extern crate glam;
+
+use glam::{Mat4, Vec3, Vec4};
+
+/// An axis-aligned bounding box, described by its minimum and maximum corners.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl BoundingBox {
+    /// Creates a new bounding box from its minimum and maximum corners.
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        BoundingBox { min, max }
+    }
+
+    /// Creates the smallest bounding box enclosing all given points. Returns `None` if there are no points.
+    pub fn from_points<I: IntoIterator<Item = Vec3>>(points: I) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+
+        Some(points.fold(BoundingBox::new(first, first), |bounds, point| BoundingBox {
+            min: bounds.min.min(point),
+            max: bounds.max.max(point),
+        }))
+    }
+
+    /// Creates a bounding box from interleaved vertex data, the same data that is passed to [`crate::Mesh::update_vertices`].
+    /// `stride` is the amount of floats per vertex and `offset` the index of the x position inside of a vertex.
+    /// Returns `None` if there are no vertices or a vertex is too small to hold a position at `offset`.
+    pub fn from_vertices(data: &[f32], stride: usize, offset: usize) -> Option<Self> {
+        Self::from_points(vertex_positions(data, stride, offset))
+    }
+
+    /// Returns the center of the bounding box.
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    /// Returns half of the size of the bounding box on each axis.
+    pub fn half_extents(&self) -> Vec3 {
+        (self.max - self.min) * 0.5
+    }
+
+    /// Returns the size of the bounding box on each axis.
+    pub fn size(&self) -> Vec3 {
+        self.max - self.min
+    }
+
+    /// Returns if the point lies inside of (or on the surface of) the bounding box.
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        point.cmpge(self.min).all() && point.cmple(self.max).all()
+    }
+
+    /// Returns if this bounding box overlaps another bounding box.
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.min.cmple(other.max).all() && self.max.cmpge(other.min).all()
+    }
+
+    /// Returns the smallest bounding box enclosing both bounding boxes.
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    /// Returns the 8 corners of the bounding box.
+    pub fn corners(&self) -> [Vec3; 8] {
+        [
+            Vec3::new(self.min.x, self.min.y, self.min.z),
+            Vec3::new(self.max.x, self.min.y, self.min.z),
+            Vec3::new(self.min.x, self.max.y, self.min.z),
+            Vec3::new(self.max.x, self.max.y, self.min.z),
+            Vec3::new(self.min.x, self.min.y, self.max.z),
+            Vec3::new(self.max.x, self.min.y, self.max.z),
+            Vec3::new(self.min.x, self.max.y, self.max.z),
+            Vec3::new(self.max.x, self.max.y, self.max.z),
+        ]
+    }
+
+    /// Transforms the bounding box by a matrix (for example a model matrix) and returns the new axis-aligned
+    /// bounding box enclosing the transformed box.
+    pub fn transform(&self, matrix: &Mat4) -> BoundingBox {
+        let center = matrix.transform_point3(self.center());
+        let half_extents = self.half_extents();
+
+        // Project the half extents onto each world axis using the absolute rotation/scale part of the matrix
+        let extents = matrix.x_axis.truncate().abs() * half_extents.x
+            + matrix.y_axis.truncate().abs() * half_extents.y
+            + matrix.z_axis.truncate().abs() * half_extents.z;
+
+        BoundingBox {
+            min: center - extents,
+            max: center + extents,
+        }
+    }
+}
+
+/// A bounding sphere, described by its center and radius.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    /// Creates a new bounding sphere from a center and radius.
+    pub fn new(center: Vec3, radius: f32) -> Self {
+        BoundingSphere { center, radius }
+    }
+
+    /// Creates a bounding sphere enclosing all given points, centered on their bounding box.
+    /// Returns `None` if there are no points.
+    pub fn from_points<I: IntoIterator<Item = Vec3> + Clone>(points: I) -> Option<Self> {
+        let center = BoundingBox::from_points(points.clone())?.center();
+        let radius = points
+            .into_iter()
+            .fold(0.0f32, |radius, point| radius.max(point.distance(center)));
+
+        Some(BoundingSphere { center, radius })
+    }
+
+    /// Creates a bounding sphere from interleaved vertex data, the same data that is passed to [`crate::Mesh::update_vertices`].
+    /// `stride` is the amount of floats per vertex and `offset` the index of the x position inside of a vertex.
+    /// Returns `None` if there are no vertices or a vertex is too small to hold a position at `offset`.
+    pub fn from_vertices(data: &[f32], stride: usize, offset: usize) -> Option<Self> {
+        Self::from_points(vertex_positions(data, stride, offset))
+    }
+
+    /// Creates the bounding sphere enclosing a bounding box.
+    pub fn from_box(bounds: &BoundingBox) -> Self {
+        BoundingSphere {
+            center: bounds.center(),
+            radius: bounds.half_extents().length(),
+        }
+    }
+
+    /// Returns if the point lies inside of (or on the surface of) the bounding sphere.
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        point.distance_squared(self.center) <= self.radius * self.radius
+    }
+
+    /// Returns if this bounding sphere overlaps another bounding sphere.
+    pub fn intersects(&self, other: &BoundingSphere) -> bool {
+        let radii = self.radius + other.radius;
+        self.center.distance_squared(other.center) <= radii * radii
+    }
+
+    /// Transforms the bounding sphere by a matrix (for example a model matrix). Non-uniform scaling
+    /// uses the largest scale axis, so the result always encloses the transformed sphere.
+    pub fn transform(&self, matrix: &Mat4) -> BoundingSphere {
+        let max_scale = matrix
+            .x_axis
+            .truncate()
+            .length()
+            .max(matrix.y_axis.truncate().length())
+            .max(matrix.z_axis.truncate().length());
+
+        BoundingSphere {
+            center: matrix.transform_point3(self.center),
+            radius: self.radius * max_scale,
+        }
+    }
+}
+
+/// A plane in the form `normal.dot(point) + distance = 0`. Points with a positive signed distance lie in front of it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plane {
+    pub normal: Vec3,
+    pub distance: f32,
+}
+
+impl Plane {
+    /// Creates a plane from the coefficients `(a, b, c, d)` of the equation `ax + by + cz + d = 0` and normalizes it.
+    pub fn from_coefficients(coefficients: Vec4) -> Self {
+        let normal = coefficients.truncate();
+        let length = normal.length();
+
+        Plane {
+            normal: normal / length,
+            distance: coefficients.w / length,
+        }
+    }
+
+    /// Returns the signed distance from the plane to the point.
+    pub fn signed_distance(&self, point: Vec3) -> f32 {
+        self.normal.dot(point) + self.distance
+    }
+}
+
+/// A view frustum made up of 6 planes pointing inwards, used to cull objects which are not visible to a camera.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    /// The planes in the order left, right, bottom, top, near, far.
+    pub planes: [Plane; 6],
+}
+
+impl Frustum {
+    /// Extracts the frustum from a view-projection matrix (`projection * view`) using OpenGL clip space conventions.
+    /// Passing a model-view-projection matrix instead results in a frustum in the models local space.
+    pub fn from_matrix(view_projection: &Mat4) -> Self {
+        let row_0 = view_projection.row(0);
+        let row_1 = view_projection.row(1);
+        let row_2 = view_projection.row(2);
+        let row_3 = view_projection.row(3);
+
+        Frustum {
+            planes: [
+                Plane::from_coefficients(row_3 + row_0), // left
+                Plane::from_coefficients(row_3 - row_0), // right
+                Plane::from_coefficients(row_3 + row_1), // bottom
+                Plane::from_coefficients(row_3 - row_1), // top
+                Plane::from_coefficients(row_3 + row_2), // near
+                Plane::from_coefficients(row_3 - row_2), // far
+            ],
+        }
+    }
+
+    /// Returns if the point lies inside of the frustum.
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.signed_distance(point) >= 0.0)
+    }
+
+    /// Returns if the bounding sphere is at least partially inside of the frustum.
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.signed_distance(sphere.center) >= -sphere.radius)
+    }
+
+    /// Returns if the bounding box is at least partially inside of the frustum. This test is conservative,
+    /// boxes close to the frustums corners may be reported as visible even though they are not.
+    pub fn intersects_box(&self, bounds: &BoundingBox) -> bool {
+        self.planes.iter().all(|plane| {
+            // The corner of the box which lies furthest along the planes normal
+            let positive_vertex = Vec3::select(plane.normal.cmpge(Vec3::ZERO), bounds.max, bounds.min);
+            plane.signed_distance(positive_vertex) >= 0.0
+        })
+    }
+}
+
+/// Returns an iterator over the positions in interleaved vertex data, which is empty if a vertex of `stride` floats
+/// can not hold a position at `offset`.
+fn vertex_positions(data: &[f32], stride: usize, offset: usize) -> impl Iterator<Item = Vec3> + Clone + '_ {
+    // A stride of 0 would make `chunks_exact` panic, so invalid strides skip all data instead
+    let data = if stride >= offset.saturating_add(3) { data } else { &[] };
+    data.chunks_exact(stride.max(1))
+        .map(move |vertex| Vec3::new(vertex[offset], vertex[offset + 1], vertex[offset + 2]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_box() -> BoundingBox {
+        BoundingBox::new(Vec3::splat(-1.0), Vec3::splat(1.0))
+    }
+
+    #[test]
+    fn box_from_vertices_skips_other_attributes() {
+        // Position followed by a texture coordinate
+        let data = [1.0, 2.0, 3.0, 9.0, 9.0, -1.0, 0.0, 5.0, -9.0, -9.0];
+        let bounds = BoundingBox::from_vertices(&data, 5, 0).unwrap();
+        assert_eq!(bounds, BoundingBox::new(Vec3::new(-1.0, 0.0, 3.0), Vec3::new(1.0, 2.0, 5.0)));
+
+        let bounds = BoundingBox::from_vertices(&data, 5, 2).unwrap();
+        assert_eq!(bounds, BoundingBox::new(Vec3::new(3.0, -9.0, -9.0), Vec3::new(5.0, 9.0, 9.0)));
+    }
+
+    #[test]
+    fn box_from_vertices_rejects_invalid_strides() {
+        let data = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        assert_eq!(BoundingBox::from_vertices(&data, 0, 0), None);
+        assert_eq!(BoundingBox::from_vertices(&data, 2, 0), None);
+        assert_eq!(BoundingBox::from_vertices(&data, 3, 1), None);
+        assert_eq!(BoundingSphere::from_vertices(&data, 0, 0), None);
+        assert_eq!(BoundingBox::from_vertices(&[], 3, 0), None);
+    }
+
+    #[test]
+    fn box_transform_encloses_rotated_box() {
+        let matrix = Mat4::from_translation(Vec3::new(10.0, 0.0, 0.0)) * Mat4::from_rotation_z(std::f32::consts::FRAC_PI_4);
+        let bounds = unit_box().transform(&matrix);
+
+        let extent = 2.0f32.sqrt();
+        assert!(bounds.min.abs_diff_eq(Vec3::new(10.0 - extent, -extent, -1.0), 1e-5));
+        assert!(bounds.max.abs_diff_eq(Vec3::new(10.0 + extent, extent, 1.0), 1e-5));
+        for corner in unit_box().corners() {
+            let corner = matrix.transform_point3(corner);
+            assert!(bounds.min.cmple(corner + 1e-5).all() && bounds.max.cmpge(corner - 1e-5).all());
+        }
+    }
+
+    #[test]
+    fn sphere_from_box_touches_corners() {
+        let bounds = BoundingBox::new(Vec3::ZERO, Vec3::new(2.0, 4.0, 4.0));
+        let sphere = BoundingSphere::from_box(&bounds);
+        assert_eq!(sphere.center, Vec3::new(1.0, 2.0, 2.0));
+        assert!((sphere.radius - 3.0).abs() < 1e-6);
+        assert!(bounds.corners().iter().all(|&corner| sphere.contains_point(corner + (sphere.center - corner) * 1e-6)));
+    }
+
+    #[test]
+    fn frustum_culls_objects_outside() {
+        let projection = Mat4::perspective_rh_gl(std::f32::consts::FRAC_PI_2, 1.0, 0.1, 100.0);
+        let view = Mat4::look_at_rh(Vec3::ZERO, Vec3::NEG_Z, Vec3::Y);
+        let frustum = Frustum::from_matrix(&(projection * view));
+
+        let in_front = BoundingBox::new(Vec3::new(-1.0, -1.0, -11.0), Vec3::new(1.0, 1.0, -9.0));
+        let behind = BoundingBox::new(Vec3::new(-1.0, -1.0, 9.0), Vec3::new(1.0, 1.0, 11.0));
+        let beyond_far = BoundingBox::new(Vec3::new(-1.0, -1.0, -202.0), Vec3::new(1.0, 1.0, -200.0));
+        let to_the_side = BoundingBox::new(Vec3::new(20.0, -1.0, -11.0), Vec3::new(22.0, 1.0, -9.0));
+        let crossing_left = BoundingBox::new(Vec3::new(-12.0, -1.0, -11.0), Vec3::new(-9.0, 1.0, -9.0));
+
+        assert!(frustum.contains_point(Vec3::new(0.0, 0.0, -10.0)));
+        assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, 10.0)));
+
+        assert!(frustum.intersects_box(&in_front));
+        assert!(frustum.intersects_box(&crossing_left));
+        assert!(!frustum.intersects_box(&behind));
+        assert!(!frustum.intersects_box(&beyond_far));
+        assert!(!frustum.intersects_box(&to_the_side));
+
+        assert!(frustum.intersects_sphere(&BoundingSphere::from_box(&in_front)));
+        assert!(frustum.intersects_sphere(&BoundingSphere::from_box(&crossing_left)));
+        assert!(!frustum.intersects_sphere(&BoundingSphere::from_box(&behind)));
+        assert!(!frustum.intersects_sphere(&BoundingSphere::from_box(&to_the_side)));
+    }
+}
@@ -0,0 +1,58 @@
+extern crate gl;
+
+use gl::types::*;
+use std::cell::Cell;
+use std::ffi::CStr;
+
+/// Parameter of `EXT_texture_filter_anisotropic`, which is not part of the core OpenGL bindings.
+const MAX_TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FF;
+
+thread_local! {
+    /// The largest supported anisotropy, queried once per thread as OpenGL contexts are current on a single thread.
+    static MAX_ANISOTROPY: Cell<Option<Option<f32>>> = const { Cell::new(None) };
+}
+
+/// Internal function returning if the current OpenGL context supports an extension, for example `GL_EXT_texture_filter_anisotropic`.
+pub(crate) fn has_extension(name: &str) -> bool {
+    unsafe {
+        let mut count = 0;
+        gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut count);
+
+        (0..count as GLuint).any(|index| {
+            let extension = gl::GetStringi(gl::EXTENSIONS, index);
+            !extension.is_null() && CStr::from_ptr(extension as *const _).to_bytes() == name.as_bytes()
+        })
+    }
+}
+
+/// Internal function returning if the current OpenGL context has at least the given version.
+pub(crate) fn has_version(major: i32, minor: i32) -> bool {
+    unsafe {
+        let (mut context_major, mut context_minor) = (0, 0);
+        gl::GetIntegerv(gl::MAJOR_VERSION, &mut context_major);
+        gl::GetIntegerv(gl::MINOR_VERSION, &mut context_minor);
+
+        (context_major, context_minor) >= (major, minor)
+    }
+}
+
+/// Internal function returning the largest anisotropy the current OpenGL context supports for texture filtering,
+/// or `None` if anisotropic filtering is not supported. The value is only queried the first time.
+pub(crate) fn max_anisotropy() -> Option<f32> {
+    MAX_ANISOTROPY.with(|cached| {
+        if let Some(max_anisotropy) = cached.get() {
+            return max_anisotropy;
+        }
+
+        let supported =
+            has_extension("GL_EXT_texture_filter_anisotropic") || has_extension("GL_ARB_texture_filter_anisotropic");
+        let max_anisotropy = supported.then(|| unsafe {
+            let mut max_anisotropy = 1.0;
+            gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut max_anisotropy);
+            max_anisotropy
+        });
+
+        cached.set(Some(max_anisotropy));
+        max_anisotropy
+    })
+}
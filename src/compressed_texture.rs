@@ -0,0 +1,607 @@
+extern crate gl;
+
+use gl::types::*;
+use std::path::Path;
+
+use crate::capabilities::{has_extension, has_version};
+
+/// Formats of `EXT_texture_compression_s3tc` and `EXT_texture_sRGB`, which are not part of the core OpenGL bindings.
+const COMPRESSED_RGB_S3TC_DXT1: GLenum = 0x83F0;
+const COMPRESSED_RGBA_S3TC_DXT1: GLenum = 0x83F1;
+const COMPRESSED_RGBA_S3TC_DXT3: GLenum = 0x83F2;
+const COMPRESSED_RGBA_S3TC_DXT5: GLenum = 0x83F3;
+const COMPRESSED_SRGB_S3TC_DXT1: GLenum = 0x8C4C;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT1: GLenum = 0x8C4D;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT3: GLenum = 0x8C4E;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT5: GLenum = 0x8C4F;
+
+const DDS_MAGIC: &[u8; 4] = b"DDS ";
+const KTX_IDENTIFIER: &[u8; 12] = &[0xAB, 0x4B, 0x54, 0x58, 0x20, 0x31, 0x31, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A];
+const KTX2_IDENTIFIER: &[u8; 12] = &[0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// Enum representing the block compressed formats a texture can be stored in on the GPU. Every format stores blocks
+/// of 4x4 pixels. BC1 to BC7 are common on desktop graphics cards, ETC2 and EAC on mobile and newer desktop drivers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressedFormat {
+    Bc1,
+    Bc1Srgb,
+    Bc1Alpha,
+    Bc1AlphaSrgb,
+    Bc2,
+    Bc2Srgb,
+    Bc3,
+    Bc3Srgb,
+    Bc4,
+    Bc4Signed,
+    Bc5,
+    Bc5Signed,
+    Bc6H,
+    Bc6HSigned,
+    Bc7,
+    Bc7Srgb,
+    Etc2Rgb8,
+    Etc2Srgb8,
+    Etc2Rgb8A1,
+    Etc2Srgb8A1,
+    Etc2Rgba8,
+    Etc2Srgb8Alpha8,
+    EacR11,
+    EacR11Signed,
+    EacRg11,
+    EacRg11Signed,
+}
+
+impl CompressedFormat {
+    /// Returns the OpenGL internal format, which is how the texture is stored on the GPU.
+    pub fn internal_format(self) -> GLenum {
+        match self {
+            CompressedFormat::Bc1 => COMPRESSED_RGB_S3TC_DXT1,
+            CompressedFormat::Bc1Srgb => COMPRESSED_SRGB_S3TC_DXT1,
+            CompressedFormat::Bc1Alpha => COMPRESSED_RGBA_S3TC_DXT1,
+            CompressedFormat::Bc1AlphaSrgb => COMPRESSED_SRGB_ALPHA_S3TC_DXT1,
+            CompressedFormat::Bc2 => COMPRESSED_RGBA_S3TC_DXT3,
+            CompressedFormat::Bc2Srgb => COMPRESSED_SRGB_ALPHA_S3TC_DXT3,
+            CompressedFormat::Bc3 => COMPRESSED_RGBA_S3TC_DXT5,
+            CompressedFormat::Bc3Srgb => COMPRESSED_SRGB_ALPHA_S3TC_DXT5,
+            CompressedFormat::Bc4 => gl::COMPRESSED_RED_RGTC1,
+            CompressedFormat::Bc4Signed => gl::COMPRESSED_SIGNED_RED_RGTC1,
+            CompressedFormat::Bc5 => gl::COMPRESSED_RG_RGTC2,
+            CompressedFormat::Bc5Signed => gl::COMPRESSED_SIGNED_RG_RGTC2,
+            CompressedFormat::Bc6H => gl::COMPRESSED_RGB_BPTC_UNSIGNED_FLOAT,
+            CompressedFormat::Bc6HSigned => gl::COMPRESSED_RGB_BPTC_SIGNED_FLOAT,
+            CompressedFormat::Bc7 => gl::COMPRESSED_RGBA_BPTC_UNORM,
+            CompressedFormat::Bc7Srgb => gl::COMPRESSED_SRGB_ALPHA_BPTC_UNORM,
+            CompressedFormat::Etc2Rgb8 => gl::COMPRESSED_RGB8_ETC2,
+            CompressedFormat::Etc2Srgb8 => gl::COMPRESSED_SRGB8_ETC2,
+            CompressedFormat::Etc2Rgb8A1 => gl::COMPRESSED_RGB8_PUNCHTHROUGH_ALPHA1_ETC2,
+            CompressedFormat::Etc2Srgb8A1 => gl::COMPRESSED_SRGB8_PUNCHTHROUGH_ALPHA1_ETC2,
+            CompressedFormat::Etc2Rgba8 => gl::COMPRESSED_RGBA8_ETC2_EAC,
+            CompressedFormat::Etc2Srgb8Alpha8 => gl::COMPRESSED_SRGB8_ALPHA8_ETC2_EAC,
+            CompressedFormat::EacR11 => gl::COMPRESSED_R11_EAC,
+            CompressedFormat::EacR11Signed => gl::COMPRESSED_SIGNED_R11_EAC,
+            CompressedFormat::EacRg11 => gl::COMPRESSED_RG11_EAC,
+            CompressedFormat::EacRg11Signed => gl::COMPRESSED_SIGNED_RG11_EAC,
+        }
+    }
+
+    /// Returns the compressed format matching an OpenGL internal format, as stored in KTX files.
+    pub fn from_internal_format(internal_format: GLenum) -> Option<Self> {
+        ALL_FORMATS
+            .iter()
+            .copied()
+            .find(|format| format.internal_format() == internal_format)
+    }
+
+    /// Returns the amount of bytes a single 4x4 block takes up.
+    pub fn block_size(self) -> usize {
+        match self {
+            CompressedFormat::Bc1
+            | CompressedFormat::Bc1Srgb
+            | CompressedFormat::Bc1Alpha
+            | CompressedFormat::Bc1AlphaSrgb
+            | CompressedFormat::Bc4
+            | CompressedFormat::Bc4Signed
+            | CompressedFormat::Etc2Rgb8
+            | CompressedFormat::Etc2Srgb8
+            | CompressedFormat::Etc2Rgb8A1
+            | CompressedFormat::Etc2Srgb8A1
+            | CompressedFormat::EacR11
+            | CompressedFormat::EacR11Signed => 8,
+            _ => 16,
+        }
+    }
+
+    /// Returns the amount of bytes a mipmap level of the given size takes up, or an error if the size does not fit
+    /// into memory, which happens for the dimensions of malformed files.
+    pub fn level_size(self, width: u32, height: u32) -> Result<usize, String> {
+        (width.div_ceil(4) as usize)
+            .checked_mul(height.div_ceil(4) as usize)
+            .and_then(|blocks| blocks.checked_mul(self.block_size()))
+            .ok_or_else(|| format!("[FerrousGl Error] A {}x{} {:?} image is too large", width, height, self))
+    }
+
+    /// Returns the amount of channels of the format.
+    pub fn channel_count(self) -> usize {
+        match self {
+            CompressedFormat::Bc4 | CompressedFormat::Bc4Signed | CompressedFormat::EacR11 | CompressedFormat::EacR11Signed => 1,
+            CompressedFormat::Bc5 | CompressedFormat::Bc5Signed | CompressedFormat::EacRg11 | CompressedFormat::EacRg11Signed => 2,
+            CompressedFormat::Bc1
+            | CompressedFormat::Bc1Srgb
+            | CompressedFormat::Bc6H
+            | CompressedFormat::Bc6HSigned
+            | CompressedFormat::Etc2Rgb8
+            | CompressedFormat::Etc2Srgb8 => 3,
+            _ => 4,
+        }
+    }
+
+    /// Returns the sRGB variant of the format, formats without an sRGB variant are returned unchanged.
+    pub fn to_srgb(self) -> Self {
+        match self {
+            CompressedFormat::Bc1 => CompressedFormat::Bc1Srgb,
+            CompressedFormat::Bc1Alpha => CompressedFormat::Bc1AlphaSrgb,
+            CompressedFormat::Bc2 => CompressedFormat::Bc2Srgb,
+            CompressedFormat::Bc3 => CompressedFormat::Bc3Srgb,
+            CompressedFormat::Bc7 => CompressedFormat::Bc7Srgb,
+            CompressedFormat::Etc2Rgb8 => CompressedFormat::Etc2Srgb8,
+            CompressedFormat::Etc2Rgb8A1 => CompressedFormat::Etc2Srgb8A1,
+            CompressedFormat::Etc2Rgba8 => CompressedFormat::Etc2Srgb8Alpha8,
+            _ => self,
+        }
+    }
+
+    /// Returns the linear variant of an sRGB format, all other formats are returned unchanged.
+    pub fn to_linear(self) -> Self {
+        ALL_FORMATS
+            .iter()
+            .copied()
+            .find(|format| format.to_srgb() == self && *format != self)
+            .unwrap_or(self)
+    }
+
+    /// Returns if the format stores sRGB encoded colors.
+    pub fn is_srgb(self) -> bool {
+        self.to_linear() != self
+    }
+
+    /// Returns if the format stores floating point (HDR) values.
+    pub fn is_float(self) -> bool {
+        matches!(self, CompressedFormat::Bc6H | CompressedFormat::Bc6HSigned)
+    }
+
+    /// Returns if the current OpenGL context can sample textures of this format. BC4 and BC5 are always supported,
+    /// BC1 to BC3 need `EXT_texture_compression_s3tc`, BC6H and BC7 OpenGL 4.2 and ETC2 and EAC OpenGL 4.3
+    /// or the matching extensions.
+    pub fn is_supported(self) -> bool {
+        match self {
+            CompressedFormat::Bc1 | CompressedFormat::Bc1Alpha | CompressedFormat::Bc2 | CompressedFormat::Bc3 => {
+                has_extension("GL_EXT_texture_compression_s3tc")
+            }
+            CompressedFormat::Bc1Srgb
+            | CompressedFormat::Bc1AlphaSrgb
+            | CompressedFormat::Bc2Srgb
+            | CompressedFormat::Bc3Srgb => {
+                has_extension("GL_EXT_texture_compression_s3tc")
+                    && (has_extension("GL_EXT_texture_sRGB") || has_extension("GL_EXT_texture_compression_s3tc_srgb"))
+            }
+            CompressedFormat::Bc4 | CompressedFormat::Bc4Signed | CompressedFormat::Bc5 | CompressedFormat::Bc5Signed => true,
+            CompressedFormat::Bc6H | CompressedFormat::Bc6HSigned | CompressedFormat::Bc7 | CompressedFormat::Bc7Srgb => {
+                has_version(4, 2) || has_extension("GL_ARB_texture_compression_bptc")
+            }
+            _ => has_version(4, 3) || has_extension("GL_ARB_ES3_compatibility"),
+        }
+    }
+
+    /// Internal function returning the compressed format matching a DXGI format of a DDS file with a DX10 header.
+    fn from_dxgi_format(dxgi_format: u32) -> Option<Self> {
+        let format = match dxgi_format {
+            70 | 71 => CompressedFormat::Bc1Alpha,
+            72 => CompressedFormat::Bc1AlphaSrgb,
+            73 | 74 => CompressedFormat::Bc2,
+            75 => CompressedFormat::Bc2Srgb,
+            76 | 77 => CompressedFormat::Bc3,
+            78 => CompressedFormat::Bc3Srgb,
+            79 | 80 => CompressedFormat::Bc4,
+            81 => CompressedFormat::Bc4Signed,
+            82 | 83 => CompressedFormat::Bc5,
+            84 => CompressedFormat::Bc5Signed,
+            94 | 95 => CompressedFormat::Bc6H,
+            96 => CompressedFormat::Bc6HSigned,
+            97 | 98 => CompressedFormat::Bc7,
+            99 => CompressedFormat::Bc7Srgb,
+            _ => return None,
+        };
+
+        Some(format)
+    }
+
+    /// Internal function returning the compressed format matching a Vulkan format of a KTX2 file.
+    fn from_vk_format(vk_format: u32) -> Option<Self> {
+        // The compressed Vulkan formats are numbered in the same order as the variants
+        match vk_format {
+            131..=156 => Some(ALL_FORMATS[(vk_format - 131) as usize]),
+            _ => None,
+        }
+    }
+}
+
+/// All compressed formats, in the order of the Vulkan format numbers.
+const ALL_FORMATS: [CompressedFormat; 26] = [
+    CompressedFormat::Bc1,
+    CompressedFormat::Bc1Srgb,
+    CompressedFormat::Bc1Alpha,
+    CompressedFormat::Bc1AlphaSrgb,
+    CompressedFormat::Bc2,
+    CompressedFormat::Bc2Srgb,
+    CompressedFormat::Bc3,
+    CompressedFormat::Bc3Srgb,
+    CompressedFormat::Bc4,
+    CompressedFormat::Bc4Signed,
+    CompressedFormat::Bc5,
+    CompressedFormat::Bc5Signed,
+    CompressedFormat::Bc6H,
+    CompressedFormat::Bc6HSigned,
+    CompressedFormat::Bc7,
+    CompressedFormat::Bc7Srgb,
+    CompressedFormat::Etc2Rgb8,
+    CompressedFormat::Etc2Srgb8,
+    CompressedFormat::Etc2Rgb8A1,
+    CompressedFormat::Etc2Srgb8A1,
+    CompressedFormat::Etc2Rgba8,
+    CompressedFormat::Etc2Srgb8Alpha8,
+    CompressedFormat::EacR11,
+    CompressedFormat::EacR11Signed,
+    CompressedFormat::EacRg11,
+    CompressedFormat::EacRg11Signed,
+];
+
+/// Block compressed image data loaded from a DDS, KTX or KTX2 file, with all of its mipmap levels.
+/// Only 2D images are supported, no cubemaps, arrays or supercompressed (Basis Universal, Zstandard) KTX2 files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompressedImage {
+    pub width: u32,
+    pub height: u32,
+    pub format: CompressedFormat,
+    /// The data of each mipmap level, starting with the full size image.
+    pub levels: Vec<Vec<u8>>,
+}
+
+impl CompressedImage {
+    /// Loads a compressed image from a DDS, KTX or KTX2 file. The container is detected from the file contents.
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Loads a compressed image from the contents of a DDS, KTX or KTX2 file.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.starts_with(DDS_MAGIC) {
+            Self::parse_dds(bytes)
+        } else if bytes.starts_with(KTX_IDENTIFIER) {
+            Self::parse_ktx(bytes)
+        } else if bytes.starts_with(KTX2_IDENTIFIER) {
+            Self::parse_ktx2(bytes)
+        } else {
+            Err("[FerrousGl Error] Unknown compressed texture container, expected a DDS, KTX or KTX2 file".to_string())
+        }
+    }
+
+    /// Internal function to parse a DDS file, with or without a DX10 header.
+    fn parse_dds(bytes: &[u8]) -> Result<Self, String> {
+        let reader = Reader::new(bytes, false);
+
+        let flags = reader.u32(8)?;
+        let height = reader.u32(12)?;
+        let width = reader.u32(16)?;
+        let pixel_format_flags = reader.u32(80)?;
+        let four_cc = reader.bytes(84, 4)?;
+        let caps2 = reader.u32(112)?;
+
+        // DDSD_MIPMAPCOUNT
+        let level_count = if flags & 0x20000 != 0 { reader.u32(28)?.max(1) } else { 1 };
+
+        // DDSCAPS2_CUBEMAP and DDSCAPS2_VOLUME
+        if caps2 & 0x200 != 0 || caps2 & 0x200000 != 0 {
+            return Err("[FerrousGl Error] Only 2D DDS textures are supported, not cubemaps or volumes".to_string());
+        }
+
+        let (format, data_offset) = match four_cc {
+            b"DX10" => {
+                let dxgi_format = reader.u32(128)?;
+                if reader.u32(140)? > 1 {
+                    return Err("[FerrousGl Error] DDS texture arrays are not supported".to_string());
+                }
+
+                let format = CompressedFormat::from_dxgi_format(dxgi_format)
+                    .ok_or_else(|| format!("[FerrousGl Error] Unsupported DXGI format {} in DDS file", dxgi_format))?;
+                (format, 148)
+            }
+            // DDPF_ALPHAPIXELS marks BC1 data using its 1-bit alpha
+            b"DXT1" if pixel_format_flags & 0x1 != 0 => (CompressedFormat::Bc1Alpha, 128),
+            b"DXT1" => (CompressedFormat::Bc1, 128),
+            b"DXT2" | b"DXT3" => (CompressedFormat::Bc2, 128),
+            b"DXT4" | b"DXT5" => (CompressedFormat::Bc3, 128),
+            b"ATI1" | b"BC4U" => (CompressedFormat::Bc4, 128),
+            b"BC4S" => (CompressedFormat::Bc4Signed, 128),
+            b"ATI2" | b"BC5U" => (CompressedFormat::Bc5, 128),
+            b"BC5S" => (CompressedFormat::Bc5Signed, 128),
+            _ => {
+                return Err(format!(
+                    "[FerrousGl Error] Unsupported DDS pixel format '{}', only block compressed formats are supported",
+                    String::from_utf8_lossy(four_cc)
+                ))
+            }
+        };
+
+        let mut offset = data_offset;
+        let levels = (0..level_count)
+            .map(|level| {
+                let size = format.level_size(
+                    width.checked_shr(level).unwrap_or(0).max(1),
+                    height.checked_shr(level).unwrap_or(0).max(1),
+                )?;
+                let data = reader.bytes(offset, size)?.to_vec();
+                offset = offset.saturating_add(size);
+                Ok(data)
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(CompressedImage {
+            width,
+            height,
+            format,
+            levels,
+        })
+    }
+
+    /// Internal function to parse a KTX (version 1) file.
+    fn parse_ktx(bytes: &[u8]) -> Result<Self, String> {
+        let swap = match Reader::new(bytes, false).u32(12)? {
+            0x04030201 => false,
+            0x01020304 => true,
+            _ => return Err("[FerrousGl Error] Invalid endianness in KTX file".to_string()),
+        };
+        let reader = Reader::new(bytes, swap);
+
+        let gl_type = reader.u32(16)?;
+        let internal_format = reader.u32(28)?;
+        let width = reader.u32(36)?;
+        let height = reader.u32(40)?.max(1);
+        let depth = reader.u32(44)?;
+        let array_elements = reader.u32(48)?;
+        let faces = reader.u32(52)?;
+        let level_count = reader.u32(56)?.max(1);
+        let key_value_length = reader.u32(60)? as usize;
+
+        if depth > 0 || array_elements > 0 || faces > 1 {
+            return Err("[FerrousGl Error] Only 2D KTX textures are supported, not cubemaps, arrays or volumes".to_string());
+        }
+
+        let format = CompressedFormat::from_internal_format(internal_format).filter(|_| gl_type == 0).ok_or_else(|| {
+            format!(
+                "[FerrousGl Error] Unsupported internal format {:#X} in KTX file, only block compressed formats are supported",
+                internal_format
+            )
+        })?;
+
+        let mut offset = 64 + key_value_length;
+        let levels = (0..level_count)
+            .map(|_| {
+                let size = reader.u32(offset)? as usize;
+                let data = reader.bytes(offset.saturating_add(4), size)?.to_vec();
+                // Levels are padded to 4 bytes
+                offset = offset.saturating_add(4).saturating_add(size.div_ceil(4) * 4);
+                Ok(data)
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(CompressedImage {
+            width,
+            height,
+            format,
+            levels,
+        })
+    }
+
+    /// Internal function to parse a KTX2 file.
+    fn parse_ktx2(bytes: &[u8]) -> Result<Self, String> {
+        let reader = Reader::new(bytes, false);
+
+        let vk_format = reader.u32(12)?;
+        let width = reader.u32(20)?;
+        let height = reader.u32(24)?.max(1);
+        let depth = reader.u32(28)?;
+        let layers = reader.u32(32)?;
+        let faces = reader.u32(36)?;
+        let level_count = reader.u32(40)?.max(1);
+        let supercompression = reader.u32(44)?;
+
+        if depth > 0 || layers > 0 || faces > 1 {
+            return Err("[FerrousGl Error] Only 2D KTX2 textures are supported, not cubemaps, arrays or volumes".to_string());
+        }
+        if supercompression != 0 {
+            return Err(format!(
+                "[FerrousGl Error] Supercompressed KTX2 files (scheme {}) are not supported",
+                supercompression
+            ));
+        }
+
+        let format = CompressedFormat::from_vk_format(vk_format).ok_or_else(|| {
+            format!(
+                "[FerrousGl Error] Unsupported Vulkan format {} in KTX2 file, only block compressed formats are supported",
+                vk_format
+            )
+        })?;
+
+        // The level index starts after the header and holds offset, length and uncompressed length of each level
+        let levels = (0..level_count as usize)
+            .map(|level| {
+                let entry = 80 + level * 24;
+                let offset = usize::try_from(reader.u64(entry)?)
+                    .map_err(|_| "[FerrousGl Error] Invalid level offset in KTX2 file".to_string())?;
+                let length = usize::try_from(reader.u64(entry + 8)?)
+                    .map_err(|_| "[FerrousGl Error] Invalid level length in KTX2 file".to_string())?;
+                Ok(reader.bytes(offset, length)?.to_vec())
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(CompressedImage {
+            width,
+            height,
+            format,
+            levels,
+        })
+    }
+}
+
+/// Internal helper to read numbers from a file with bounds checks.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    swap: bool,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8], swap: bool) -> Self {
+        Reader { bytes, swap }
+    }
+
+    fn bytes(&self, offset: usize, length: usize) -> Result<&'a [u8], String> {
+        let end = offset
+            .checked_add(length)
+            .ok_or_else(|| "[FerrousGl Error] Invalid data range in compressed texture file".to_string())?;
+
+        self.bytes
+            .get(offset..end)
+            .ok_or_else(|| "[FerrousGl Error] Compressed texture file is truncated".to_string())
+    }
+
+    fn u32(&self, offset: usize) -> Result<u32, String> {
+        let value = u32::from_le_bytes(self.bytes(offset, 4)?.try_into().unwrap());
+        Ok(if self.swap { value.swap_bytes() } else { value })
+    }
+
+    fn u64(&self, offset: usize) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.bytes(offset, 8)?.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a DDS file with a legacy header and the given four character code.
+    fn dds(width: u32, height: u32, level_count: u32, four_cc: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0u8; 128];
+        bytes[..4].copy_from_slice(DDS_MAGIC);
+        bytes[4..8].copy_from_slice(&124u32.to_le_bytes());
+        bytes[8..12].copy_from_slice(&0x20000u32.to_le_bytes());
+        bytes[12..16].copy_from_slice(&height.to_le_bytes());
+        bytes[16..20].copy_from_slice(&width.to_le_bytes());
+        bytes[28..32].copy_from_slice(&level_count.to_le_bytes());
+        bytes[84..88].copy_from_slice(four_cc);
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    /// Builds a little endian KTX file with the given internal format and levels.
+    fn ktx(width: u32, height: u32, internal_format: GLenum, levels: &[&[u8]]) -> Vec<u8> {
+        let mut bytes = KTX_IDENTIFIER.to_vec();
+        let header = [0x04030201, 0, 1, 0, internal_format, 0, width, height, 0, 0, 1, levels.len() as u32, 0];
+        bytes.extend(header.iter().flat_map(|value: &u32| value.to_le_bytes()));
+        for level in levels {
+            bytes.extend_from_slice(&(level.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(level);
+            bytes.resize(bytes.len().div_ceil(4) * 4, 0);
+        }
+        bytes
+    }
+
+    /// Builds a KTX2 file with the given Vulkan format and levels, placed after the level index.
+    fn ktx2(width: u32, height: u32, vk_format: u32, levels: &[&[u8]]) -> Vec<u8> {
+        let mut bytes = KTX2_IDENTIFIER.to_vec();
+        let header = [vk_format, 1, width, height, 0, 0, 1, levels.len() as u32, 0];
+        bytes.extend(header.iter().flat_map(|value: &u32| value.to_le_bytes()));
+        bytes.resize(80, 0);
+
+        let mut offset = 80 + levels.len() * 24;
+        for level in levels {
+            for value in [offset, level.len(), level.len()] {
+                bytes.extend_from_slice(&(value as u64).to_le_bytes());
+            }
+            offset += level.len();
+        }
+        for level in levels {
+            bytes.extend_from_slice(level);
+        }
+        bytes
+    }
+
+    #[test]
+    fn level_size_rounds_up_to_blocks() {
+        assert_eq!(CompressedFormat::Bc1.level_size(1, 1), Ok(8));
+        assert_eq!(CompressedFormat::Bc1.level_size(5, 4), Ok(16));
+        assert_eq!(CompressedFormat::Bc7.level_size(8, 8), Ok(64));
+        assert!(CompressedFormat::Bc7.level_size(u32::MAX, u32::MAX).is_err());
+    }
+
+    #[test]
+    fn parses_dds() {
+        let data = [1u8; 8 * 4 + 8 + 8];
+        let image = CompressedImage::from_bytes(&dds(8, 8, 3, b"DXT1", &data)).unwrap();
+
+        assert_eq!((image.width, image.height, image.format), (8, 8, CompressedFormat::Bc1));
+        assert_eq!(image.levels.iter().map(Vec::len).collect::<Vec<_>>(), [32, 8, 8]);
+    }
+
+    #[test]
+    fn rejects_truncated_and_huge_dds() {
+        assert!(CompressedImage::from_bytes(&dds(8, 8, 1, b"DXT5", &[0; 63])).is_err());
+        assert!(CompressedImage::from_bytes(&dds(8, 8, 1, b"DXT5", &[0; 64])[..100]).is_err());
+        assert!(CompressedImage::from_bytes(&dds(1 << 28, 1 << 28, 1, b"DXT1", &[0; 16])).is_err());
+        assert!(CompressedImage::from_bytes(&dds(u32::MAX, u32::MAX, 32, b"DXT5", &[0; 16])).is_err());
+    }
+
+    #[test]
+    fn parses_ktx() {
+        let internal_format = CompressedFormat::Bc4.internal_format();
+        let image = CompressedImage::from_bytes(&ktx(4, 4, internal_format, &[&[7; 8], &[9; 8]])).unwrap();
+
+        assert_eq!((image.width, image.height, image.format), (4, 4, CompressedFormat::Bc4));
+        assert_eq!(image.levels, [vec![7; 8], vec![9; 8]]);
+    }
+
+    #[test]
+    fn rejects_truncated_and_huge_ktx() {
+        let internal_format = CompressedFormat::Bc4.internal_format();
+        let bytes = ktx(4, 4, internal_format, &[&[7; 8]]);
+        assert!(CompressedImage::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(CompressedImage::from_bytes(&bytes[..40]).is_err());
+
+        // A level claiming to be larger than the file
+        let mut bytes = ktx(u32::MAX, u32::MAX, internal_format, &[&[7; 8]]);
+        bytes[64..68].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(CompressedImage::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn parses_ktx2() {
+        // 145 is BC7, the 15th compressed Vulkan format
+        let image = CompressedImage::from_bytes(&ktx2(4, 4, 145, &[&[3; 16]])).unwrap();
+
+        assert_eq!((image.width, image.height, image.format), (4, 4, CompressedFormat::Bc7));
+        assert_eq!(image.levels, [vec![3; 16]]);
+    }
+
+    #[test]
+    fn rejects_truncated_and_huge_ktx2() {
+        let bytes = ktx2(4, 4, 145, &[&[3; 16]]);
+        assert!(CompressedImage::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(CompressedImage::from_bytes(&bytes[..90]).is_err());
+
+        // A level offset and length near the end of the address space
+        let mut bytes = ktx2(u32::MAX, u32::MAX, 145, &[&[3; 16]]);
+        bytes[80..88].copy_from_slice(&u64::MAX.to_le_bytes());
+        bytes[88..96].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(CompressedImage::from_bytes(&bytes).is_err());
+    }
+}
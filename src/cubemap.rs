@@ -0,0 +1,508 @@
+extern crate gl;
+extern crate glam;
+extern crate image;
+
+use gl::types::*;
+use glam::{Mat4, Vec3};
+use image::DynamicImage;
+use std::cell::Cell;
+use std::path::Path;
+use std::ptr;
+
+use crate::fullscreen::{FullscreenTriangle, FULLSCREEN_VERTEX_SHADER};
+use crate::sampler::{ParameterTarget, SamplerSettings, WrapMode};
+use crate::{Shader, Texture, TextureConfig, TextureFormat};
+
+/// Represents an OpenGL cubemap texture, made up of six square faces. Cubemaps are used for skyboxes,
+/// environment reflections and point light shadows, and are sampled with `samplerCube` in shaders.
+pub struct CubemapTexture {
+    pub(crate) id: GLuint,
+    size: u32,
+    format: TextureFormat,
+    framebuffer_id: Cell<GLuint>,
+    depth_renderbuffer_id: Cell<GLuint>,
+}
+
+impl CubemapTexture {
+    /// Creates an empty cubemap with faces of the specified size and the given texture format.
+    /// Depth formats can be used for point light shadows.
+    pub fn new_empty(size: u32, format: TextureFormat) -> Result<Self, String> {
+        if format.is_compressed() {
+            return Err(format!("[FerrousGl Error] Can not create an empty {:?} cubemap", format));
+        }
+
+        let cubemap = Self::allocate(size, format);
+        cubemap.set_sampler_settings(&SamplerSettings::with_wrap(WrapMode::ClampToEdge));
+        Ok(cubemap)
+    }
+
+    /// Creates a new cubemap from six image files, in the order +X, -X, +Y, -Y, +Z, -Z (right, left, top, bottom, front, back).
+    pub fn from_files(paths: [&Path; 6], config: &TextureConfig) -> Result<Self, String> {
+        let images = paths
+            .iter()
+            .map(|path| image::open(path).map_err(|e| e.to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::from_images([&images[0], &images[1], &images[2], &images[3], &images[4], &images[5]], config)
+    }
+
+    /// Creates a new cubemap from six in-memory images, in the order +X, -X, +Y, -Y, +Z, -Z (right, left, top, bottom, front, back).
+    /// All images have to be square and of the same size.
+    pub fn from_images(images: [&DynamicImage; 6], config: &TextureConfig) -> Result<Self, String> {
+        let size = images[0].width();
+        if images.iter().any(|img| img.width() != size || img.height() != size) {
+            return Err("[FerrousGl Error] All cubemap faces have to be square and of the same size".to_string());
+        }
+
+        let format = TextureFormat::from_image_expanded(images[0], config);
+        let cubemap = Self::allocate(size, format);
+
+        for (face, img) in CubemapFace::ALL.iter().zip(images.iter()) {
+            cubemap.update_face(*face, img)?;
+        }
+
+        cubemap.finish(config);
+        Ok(cubemap)
+    }
+
+    /// Creates a new cubemap from an image file holding all six faces, see [`CubemapTexture::from_layout_image`].
+    pub fn from_layout_file(path: &Path, config: &TextureConfig) -> Result<Self, String> {
+        let img = image::open(path).map_err(|e| e.to_string())?;
+        Self::from_layout_image(&img, config)
+    }
+
+    /// Creates a new cubemap from a single image holding all six faces. The layout is detected from the aspect ratio
+    /// of the image, see [`CubemapLayout`] for the supported layouts.
+    pub fn from_layout_image(img: &DynamicImage, config: &TextureConfig) -> Result<Self, String> {
+        let layout = CubemapLayout::detect(img.width(), img.height()).ok_or_else(|| {
+            format!(
+                "[FerrousGl Error] Can not detect the cubemap layout of a {}x{} image",
+                img.width(),
+                img.height()
+            )
+        })?;
+
+        let face_size = layout.face_size(img.width(), img.height());
+        let faces = layout
+            .face_positions(face_size)
+            .iter()
+            .enumerate()
+            .map(|(index, &(x, y))| {
+                let face = img.crop_imm(x, y, face_size, face_size);
+                // The back face of a vertical cross is stored upside down
+                if layout == CubemapLayout::VerticalCross && index == 5 {
+                    face.rotate180()
+                } else {
+                    face
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Self::from_images([&faces[0], &faces[1], &faces[2], &faces[3], &faces[4], &faces[5]], config)
+    }
+
+    /// Creates a new cubemap from an equirectangular (latitude-longitude) image file, such as `.hdr` environment maps.
+    pub fn from_equirectangular_file(path: &Path, face_size: u32, config: &TextureConfig) -> Result<Self, String> {
+        let img = image::open(path).map_err(|e| e.to_string())?;
+        Self::from_equirectangular(&img, face_size, config)
+    }
+
+    /// Creates a new cubemap from an equirectangular (latitude-longitude) image by projecting it onto each face on the GPU.
+    /// HDR images keep their range, they are converted into a [`TextureFormat::Rgba16F`] cubemap.
+    pub fn from_equirectangular(img: &DynamicImage, face_size: u32, config: &TextureConfig) -> Result<Self, String> {
+        let source = Texture::from_image_with_config(
+            img,
+            &TextureConfig {
+                mipmaps: false,
+                ..config.clone()
+            },
+        )?;
+
+        let format = if source.format.is_float() {
+            TextureFormat::Rgba16F
+        } else {
+            TextureFormat::from_image_expanded(img, config)
+        };
+        let cubemap = Self::allocate(face_size, format);
+
+        let shader = Shader::new_from_source(FULLSCREEN_VERTEX_SHADER, EQUIRECTANGULAR_FRAGMENT_SHADER);
+        let triangle = FullscreenTriangle::new();
+
+        unsafe {
+            let mut previous_viewport = [0; 4];
+            gl::GetIntegerv(gl::VIEWPORT, previous_viewport.as_mut_ptr());
+            let (mut previous_read_framebuffer, mut previous_draw_framebuffer) = (0, 0);
+            gl::GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut previous_read_framebuffer);
+            gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut previous_draw_framebuffer);
+            let depth_test = gl::IsEnabled(gl::DEPTH_TEST) == gl::TRUE;
+            let srgb_conversion = gl::IsEnabled(gl::FRAMEBUFFER_SRGB) == gl::TRUE;
+
+            gl::Disable(gl::DEPTH_TEST);
+            // Linear colors have to be encoded when they are written into sRGB faces
+            if format.is_srgb() {
+                gl::Enable(gl::FRAMEBUFFER_SRGB);
+            }
+
+            shader.bind_program();
+            source.bind(0);
+            shader.set_uniform_texture("equirectangularMap", 0);
+            shader.set_uniform_1f("faceSize", face_size as f32);
+
+            for face in CubemapFace::ALL {
+                cubemap.bind_face(face);
+                shader.set_uniform_1i("face", face as i32);
+                triangle.draw();
+            }
+
+            // The conversion may run while a render texture is bound, which has to stay bound afterwards
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, previous_read_framebuffer as GLuint);
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, previous_draw_framebuffer as GLuint);
+
+            source.unbind();
+            shader.unbind_program();
+
+            if depth_test {
+                gl::Enable(gl::DEPTH_TEST);
+            }
+            if !srgb_conversion {
+                gl::Disable(gl::FRAMEBUFFER_SRGB);
+            }
+            gl::Viewport(previous_viewport[0], previous_viewport[1], previous_viewport[2], previous_viewport[3]);
+        }
+
+        cubemap.finish(config);
+        Ok(cubemap)
+    }
+
+    /// Internal function to create the cubemap storage for all faces.
+    fn allocate(size: u32, format: TextureFormat) -> Self {
+        let mut texture_id = 0;
+
+        unsafe {
+            gl::GenTextures(1, &mut texture_id);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, texture_id);
+
+            for face in CubemapFace::ALL {
+                gl::TexImage2D(
+                    face.target(),
+                    0,
+                    format.internal_format() as i32,
+                    size as i32,
+                    size as i32,
+                    0,
+                    format.pixel_format(),
+                    format.pixel_type(),
+                    ptr::null(),
+                );
+            }
+
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MIN_FILTER, format.default_filter() as i32);
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MAG_FILTER, format.default_filter() as i32);
+
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, 0);
+        }
+
+        CubemapTexture {
+            id: texture_id,
+            size,
+            format,
+            framebuffer_id: Cell::new(0),
+            depth_renderbuffer_id: Cell::new(0),
+        }
+    }
+
+    /// Internal function to apply the sampler settings and mipmaps of a config after the faces were filled.
+    fn finish(&self, config: &TextureConfig) {
+        self.set_sampler_settings(&config.sampler);
+        if config.mipmaps {
+            self.generate_mipmaps();
+        }
+    }
+
+    /// Replaces the pixels of a face with an image, which is converted into the format of the cubemap.
+    pub fn update_face(&self, face: CubemapFace, img: &DynamicImage) -> Result<(), String> {
+        if img.width() != self.size || img.height() != self.size {
+            return Err(format!(
+                "[FerrousGl Error] Expected a {}x{} image for the cubemap face, but got {}x{}",
+                self.size,
+                self.size,
+                img.width(),
+                img.height()
+            ));
+        }
+
+        let data = self.format.image_data(img)?;
+
+        self.with_binding(|| unsafe {
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TexSubImage2D(
+                face.target(),
+                0,
+                0,
+                0,
+                self.size as i32,
+                self.size as i32,
+                self.format.pixel_format(),
+                self.format.pixel_type(),
+                data.as_ptr() as *const GLvoid,
+            );
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+        });
+
+        Ok(())
+    }
+
+    /// Binds a face of the cubemap as the current framebuffer, so it can be rendered into. Color cubemaps get a depth
+    /// buffer shared between all faces, depth cubemaps are rendered into directly (for example for point light shadows).
+    /// Use [`CubemapFace::view_matrix`] and a 90° perspective projection to render the scene as seen from each face.
+    pub fn bind_face(&self, face: CubemapFace) {
+        unsafe {
+            if self.framebuffer_id.get() == 0 {
+                let mut framebuffer_id = 0;
+                gl::GenFramebuffers(1, &mut framebuffer_id);
+                self.framebuffer_id.set(framebuffer_id);
+
+                gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer_id);
+
+                if self.format.is_depth() {
+                    gl::DrawBuffer(gl::NONE);
+                    gl::ReadBuffer(gl::NONE);
+                } else {
+                    let mut renderbuffer_id = 0;
+                    gl::GenRenderbuffers(1, &mut renderbuffer_id);
+                    gl::BindRenderbuffer(gl::RENDERBUFFER, renderbuffer_id);
+                    gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH_COMPONENT24, self.size as i32, self.size as i32);
+                    gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+                    gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, gl::RENDERBUFFER, renderbuffer_id);
+                    self.depth_renderbuffer_id.set(renderbuffer_id);
+                }
+            }
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer_id.get());
+
+            let attachment = if self.format.has_stencil() {
+                gl::DEPTH_STENCIL_ATTACHMENT
+            } else if self.format.is_depth() {
+                gl::DEPTH_ATTACHMENT
+            } else {
+                gl::COLOR_ATTACHMENT0
+            };
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER, attachment, face.target(), self.id, 0);
+
+            gl::Viewport(0, 0, self.size as i32, self.size as i32);
+        }
+    }
+
+    /// Unbinds the cubemap face (binds the default framebuffer), after this normal rendering can continue.
+    pub fn unbind(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+    }
+
+    /// Binds the cubemap to a specific texture unit which can be used to set a uniform texture.
+    pub fn bind_texture(&self, texture_unit: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + texture_unit);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.id);
+        }
+    }
+
+    /// Unbinds the cubemap texture.
+    pub fn unbind_texture(&self) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, 0);
+        }
+    }
+
+    /// Sets how the cubemap is sampled in shaders. The cubemap does not have to be bound for this.
+    pub fn set_sampler_settings(&self, settings: &SamplerSettings) {
+        self.with_binding(|| settings.apply(ParameterTarget::Texture(gl::TEXTURE_CUBE_MAP)));
+    }
+
+    /// Generates all mipmap levels of every face, for example after rendering into the faces.
+    pub fn generate_mipmaps(&self) {
+        if self.format.is_integer() || self.format.is_depth() {
+            return;
+        }
+
+        self.with_binding(|| unsafe {
+            gl::GenerateMipmap(gl::TEXTURE_CUBE_MAP);
+        });
+    }
+
+    /// Returns the width and height of a single face.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Returns the format the cubemap is stored in on the GPU.
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    /// Internal function to run OpenGL calls with the cubemap bound, restoring the previously bound cubemap afterwards.
+    fn with_binding<R>(&self, f: impl FnOnce() -> R) -> R {
+        unsafe {
+            let mut previous_texture = 0;
+            gl::GetIntegerv(gl::TEXTURE_BINDING_CUBE_MAP, &mut previous_texture);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.id);
+
+            let result = f();
+
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, previous_texture as GLuint);
+            result
+        }
+    }
+}
+
+impl Drop for CubemapTexture {
+    /// Cleans up the cubemap and its framebuffer when it goes out of scope.
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.id);
+
+            if self.framebuffer_id.get() != 0 {
+                gl::DeleteFramebuffers(1, &self.framebuffer_id.get());
+            }
+            if self.depth_renderbuffer_id.get() != 0 {
+                gl::DeleteRenderbuffers(1, &self.depth_renderbuffer_id.get());
+            }
+        }
+    }
+}
+
+/// Enum representing the six faces of a cubemap, in the order OpenGL stores them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CubemapFace {
+    PositiveX,
+    NegativeX,
+    PositiveY,
+    NegativeY,
+    PositiveZ,
+    NegativeZ,
+}
+
+impl CubemapFace {
+    /// All faces in the order OpenGL stores them.
+    pub const ALL: [CubemapFace; 6] = [
+        CubemapFace::PositiveX,
+        CubemapFace::NegativeX,
+        CubemapFace::PositiveY,
+        CubemapFace::NegativeY,
+        CubemapFace::PositiveZ,
+        CubemapFace::NegativeZ,
+    ];
+
+    /// Returns the OpenGL texture target of the face.
+    pub fn target(self) -> GLenum {
+        gl::TEXTURE_CUBE_MAP_POSITIVE_X + self as GLenum
+    }
+
+    /// Returns the direction the face is looking at, seen from the center of the cubemap.
+    pub fn direction(self) -> Vec3 {
+        match self {
+            CubemapFace::PositiveX => Vec3::X,
+            CubemapFace::NegativeX => Vec3::NEG_X,
+            CubemapFace::PositiveY => Vec3::Y,
+            CubemapFace::NegativeY => Vec3::NEG_Y,
+            CubemapFace::PositiveZ => Vec3::Z,
+            CubemapFace::NegativeZ => Vec3::NEG_Z,
+        }
+    }
+
+    /// Returns the view matrix for rendering the face from a position, matching the orientation OpenGL expects
+    /// for cubemap faces. Combine it with `Mat4::perspective_rh_gl(90.0f32.to_radians(), 1.0, near, far)`.
+    pub fn view_matrix(self, position: Vec3) -> Mat4 {
+        let up = match self {
+            CubemapFace::PositiveY => Vec3::Z,
+            CubemapFace::NegativeY => Vec3::NEG_Z,
+            _ => Vec3::NEG_Y,
+        };
+
+        Mat4::look_at_rh(position, position + self.direction(), up)
+    }
+}
+
+/// Enum representing the ways six cubemap faces can be arranged in a single image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CubemapLayout {
+    /// A 4:3 image with the faces arranged as a horizontal cross:
+    /// `+Y` on top, `-X +Z +X -Z` in the middle row and `-Y` on the bottom.
+    HorizontalCross,
+    /// A 3:4 image with the faces arranged as a vertical cross: `+Y` on top, `-X +Z +X` in the second row,
+    /// `-Y` in the third row and `-Z` upside down in the last row.
+    VerticalCross,
+    /// A 6:1 image with the faces next to each other in the order +X, -X, +Y, -Y, +Z, -Z.
+    HorizontalStrip,
+    /// A 1:6 image with the faces above each other in the order +X, -X, +Y, -Y, +Z, -Z.
+    VerticalStrip,
+}
+
+impl CubemapLayout {
+    /// Detects the layout of an image from its aspect ratio.
+    pub fn detect(width: u32, height: u32) -> Option<Self> {
+        if width * 3 == height * 4 {
+            Some(CubemapLayout::HorizontalCross)
+        } else if width * 4 == height * 3 {
+            Some(CubemapLayout::VerticalCross)
+        } else if width == height * 6 {
+            Some(CubemapLayout::HorizontalStrip)
+        } else if width * 6 == height {
+            Some(CubemapLayout::VerticalStrip)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the size of a single face in an image of the given size.
+    pub fn face_size(self, width: u32, height: u32) -> u32 {
+        match self {
+            CubemapLayout::HorizontalCross => width / 4,
+            CubemapLayout::VerticalCross => width / 3,
+            CubemapLayout::HorizontalStrip => height,
+            CubemapLayout::VerticalStrip => width,
+        }
+    }
+
+    /// Returns the top left pixel of every face in the image, in the order +X, -X, +Y, -Y, +Z, -Z.
+    pub fn face_positions(self, face_size: u32) -> [(u32, u32); 6] {
+        let cells = match self {
+            CubemapLayout::HorizontalCross => [(2, 1), (0, 1), (1, 0), (1, 2), (1, 1), (3, 1)],
+            CubemapLayout::VerticalCross => [(2, 1), (0, 1), (1, 0), (1, 2), (1, 1), (1, 3)],
+            CubemapLayout::HorizontalStrip => [(0, 0), (1, 0), (2, 0), (3, 0), (4, 0), (5, 0)],
+            CubemapLayout::VerticalStrip => [(0, 0), (0, 1), (0, 2), (0, 3), (0, 4), (0, 5)],
+        };
+
+        cells.map(|(column, row)| (column * face_size, row * face_size))
+    }
+}
+
+/// Fragment shader projecting an equirectangular image onto a cubemap face.
+const EQUIRECTANGULAR_FRAGMENT_SHADER: &str = r#"#version 330 core
+out vec4 FragColor;
+
+uniform sampler2D equirectangularMap;
+uniform int face;
+uniform float faceSize;
+
+const float PI = 3.14159265359;
+
+vec3 faceDirection(int face, vec2 uv) {
+    if (face == 0) return vec3(1.0, -uv.y, -uv.x);
+    if (face == 1) return vec3(-1.0, -uv.y, uv.x);
+    if (face == 2) return vec3(uv.x, 1.0, uv.y);
+    if (face == 3) return vec3(uv.x, -1.0, -uv.y);
+    if (face == 4) return vec3(uv.x, -uv.y, 1.0);
+    return vec3(-uv.x, -uv.y, -1.0);
+}
+
+void main() {
+    vec2 uv = gl_FragCoord.xy / faceSize * 2.0 - 1.0;
+    vec3 direction = normalize(faceDirection(face, uv));
+
+    // Image rows are stored top to bottom, so the top of the sky is at the start of the texture
+    vec2 equirectangularUv = vec2(atan(direction.z, direction.x) / (2.0 * PI) + 0.5, 0.5 - asin(direction.y) / PI);
+    FragColor = vec4(texture(equirectangularMap, equirectangularUv).rgb, 1.0);
+}
+"#;
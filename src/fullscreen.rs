@@ -0,0 +1,75 @@
+extern crate gl;
+
+use gl::types::*;
+
+/// Vertex shader for drawing a triangle covering the whole viewport without any vertex data.
+/// Passes `TexCoord` in the range `[0, 1]` across the viewport to the fragment shader.
+pub(crate) const FULLSCREEN_VERTEX_SHADER: &str = r#"#version 330 core
+out vec2 TexCoord;
+
+void main() {
+    vec2 position = vec2(float((gl_VertexID << 1) & 2), float(gl_VertexID & 2));
+    TexCoord = position;
+    gl_Position = vec4(position * 2.0 - 1.0, 0.0, 1.0);
+}
+"#;
+
+/// Fragment shader copying `sourceTexture` unchanged, used for drawing textures onto the viewport. The texture
+/// coordinates are mapped to the region starting at `uvOffset` with the size `uvScale`, which has to be set to `(1, 1)`
+/// to copy the whole texture. If `encodeSrgb` is set, linear colors are encoded to sRGB while copying.
+pub(crate) const PASSTHROUGH_FRAGMENT_SHADER: &str = r#"#version 330 core
+in vec2 TexCoord;
+out vec4 FragColor;
+
+uniform sampler2D sourceTexture;
+uniform vec2 uvOffset;
+uniform vec2 uvScale;
+uniform bool encodeSrgb;
+
+vec3 linearToSrgb(vec3 color) {
+    return mix(color * 12.92, 1.055 * pow(color, vec3(1.0 / 2.4)) - 0.055, step(0.0031308, color));
+}
+
+void main() {
+    vec4 color = texture(sourceTexture, uvOffset + TexCoord * uvScale);
+    if (encodeSrgb) {
+        color.rgb = linearToSrgb(color.rgb);
+    }
+    FragColor = color;
+}
+"#;
+
+/// Internal helper to draw a single triangle covering the whole viewport, used for fullscreen passes.
+/// The core profile requires a vertex array to be bound for drawing, even if it holds no attributes.
+pub(crate) struct FullscreenTriangle {
+    vertex_array: GLuint,
+}
+
+impl FullscreenTriangle {
+    pub(crate) fn new() -> Self {
+        let mut vertex_array = 0;
+
+        unsafe {
+            gl::GenVertexArrays(1, &mut vertex_array);
+        }
+
+        FullscreenTriangle { vertex_array }
+    }
+
+    /// Draws the triangle using the currently bound shader program.
+    pub(crate) fn draw(&self) {
+        unsafe {
+            gl::BindVertexArray(self.vertex_array);
+            gl::DrawArrays(gl::TRIANGLES, 0, 3);
+            gl::BindVertexArray(0);
+        }
+    }
+}
+
+impl Drop for FullscreenTriangle {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.vertex_array);
+        }
+    }
+}
@@ -3,17 +3,74 @@ pub mod mesh;
 pub mod shader;
 pub mod texture;
 pub mod render_texture;
+pub mod bounds;
+pub mod pixel_buffer;
+pub mod sampler;
+pub mod cubemap;
+pub mod texture_array;
+pub mod texture_atlas;
+pub mod compressed_texture;
+pub mod skybox;
+pub mod tonemapping;
+pub mod blit;
+pub mod post_processing;
+pub mod shadow_map;
+mod capabilities;
+mod fullscreen;
 
 pub use window::GlWindow;
 pub use window::RenderingType;
 pub use window::DepthType;
 pub use window::BlendMode;
 pub use window::BlendFactor;
+pub use window::StencilOp;
 pub use window::WindowKey;
 pub use window::WindowConfig;
 pub use mesh::Mesh;
 pub use shader::Shader;
+pub use shader::TransformFeedbackMode;
 pub use texture::Texture;
 pub use texture::MipmapType;
 pub use texture::FilterMode;
-pub use render_texture::RenderTexture;
\ No newline at end of file
+pub use texture::TextureFormat;
+pub use texture::TextureConfig;
+pub use texture::DepthVisualization;
+pub use compressed_texture::CompressedFormat;
+pub use compressed_texture::CompressedImage;
+pub use render_texture::RenderTexture;
+pub use render_texture::RenderTextureBuilder;
+pub use render_texture::DepthAttachment;
+pub use render_texture::RenderTextureGuard;
+pub use blit::blit;
+pub use blit::Rect;
+pub use blit::BlitMask;
+pub use blit::ScalingMode;
+pub use cubemap::CubemapTexture;
+pub use cubemap::CubemapFace;
+pub use cubemap::CubemapLayout;
+pub use skybox::Skybox;
+pub use tonemapping::Tonemapper;
+pub use tonemapping::TonemapOperator;
+pub use post_processing::PostProcessChain;
+pub use post_processing::PostProcessPass;
+pub use post_processing::PostEffect;
+pub use post_processing::UniformValue;
+pub use shadow_map::ShadowMap;
+pub use shadow_map::ShadowLight;
+pub use shadow_map::CascadedShadowMap;
+pub use shadow_map::CascadeCamera;
+pub use texture_array::TextureArray;
+pub use texture_array::Texture3D;
+pub use texture_atlas::TextureAtlas;
+pub use texture_atlas::TextureAtlasBuilder;
+pub use texture_atlas::AtlasConfig;
+pub use texture_atlas::AtlasRegion;
+pub use sampler::Sampler;
+pub use sampler::SamplerSettings;
+pub use sampler::WrapMode;
+pub use sampler::CompareFunction;
+pub use pixel_buffer::PixelUploadBuffer;
+pub use pixel_buffer::PixelReadbackBuffer;
+pub use bounds::BoundingBox;
+pub use bounds::BoundingSphere;
+pub use bounds::Frustum;
\ No newline at end of file
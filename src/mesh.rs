@@ -5,12 +5,16 @@ extern crate glfw;
 use gl::types::*;
 use std::mem;
 
+use crate::bounds::{BoundingBox, BoundingSphere};
+
 /// A struct to handle a mesh for rendering.
 pub struct Mesh {
     vertex_array: u32,
-    vertex_buffer: u32,
+    pub(crate) vertex_buffer: u32,
     index_buffer: u32,
     pub(crate) indices_length: usize,
+    bounds: Option<BoundingBox>,
+    bounding_sphere: Option<BoundingSphere>,
 }
 
 impl Mesh {
@@ -41,6 +45,8 @@ impl Mesh {
             vertex_buffer,
             index_buffer,
             indices_length: 0,
+            bounds: None,
+            bounding_sphere: None,
         }
     }
 
@@ -86,7 +92,8 @@ impl Mesh {
         }
     }
 
-    /// Updates the vertex data.
+    /// Updates the vertex data. The bounding box and bounding sphere of the mesh are left unchanged, so they are stale
+    /// if the positions changed. Use [`Mesh::update_vertices_with_bounds`] or [`Mesh::set_bounds`] to keep them in sync.
     pub fn update_vertices(&self, data: &[f32]) {
         unsafe {
             gl::BindBuffer(gl::ARRAY_BUFFER, self.vertex_buffer);
@@ -100,6 +107,47 @@ impl Mesh {
         }
     }
 
+    /// Allocates room for `length` floats in the vertex buffer without uploading any data. This is used to prepare
+    /// a mesh as the destination of [`crate::GlWindow::capture_transform_feedback`].
+    pub fn allocate_vertices(&self, length: usize) {
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vertex_buffer);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                (length * mem::size_of::<f32>()) as GLsizeiptr,
+                std::ptr::null(),
+                gl::DYNAMIC_COPY,
+            );
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        }
+    }
+
+    /// Updates the vertex data and computes the bounding box and bounding sphere of the mesh from it.
+    /// `stride` is the amount of floats per vertex, the first three floats of every vertex are treated as its position.
+    pub fn update_vertices_with_bounds(&mut self, data: &[f32], stride: usize) {
+        self.update_vertices(data);
+
+        self.bounds = BoundingBox::from_vertices(data, stride, 0);
+        self.bounding_sphere = BoundingSphere::from_vertices(data, stride, 0);
+    }
+
+    /// Sets the bounding box of the mesh manually, for example when the vertex data is generated on the GPU.
+    /// The bounding sphere is derived from the bounding box.
+    pub fn set_bounds(&mut self, bounds: BoundingBox) {
+        self.bounds = Some(bounds);
+        self.bounding_sphere = Some(BoundingSphere::from_box(&bounds));
+    }
+
+    /// Returns the bounding box of the mesh in local space if it is known.
+    pub fn bounds(&self) -> Option<BoundingBox> {
+        self.bounds
+    }
+
+    /// Returns the bounding sphere of the mesh in local space if it is known.
+    pub fn bounding_sphere(&self) -> Option<BoundingSphere> {
+        self.bounding_sphere
+    }
+
     /// Updates the indices.
     pub fn update_indices(&mut self, indices: &[u32]) {
         self.indices_length = indices.len();
@@ -116,6 +164,40 @@ impl Mesh {
         }
     }
 
+    /// Reads the vertex data back from the GPU. This is useful for inspecting data written by the GPU,
+    /// for example through transform feedback. Note that this stalls until the GPU has finished writing the buffer.
+    pub fn read_vertices(&self) -> Vec<f32> {
+        Self::read_buffer(self.vertex_buffer)
+    }
+
+    /// Reads the indices back from the GPU. Note that this stalls until the GPU has finished writing the buffer.
+    pub fn read_indices(&self) -> Vec<u32> {
+        Self::read_buffer(self.index_buffer)
+    }
+
+    /// Internal function to copy the contents of a buffer back to the CPU.
+    fn read_buffer<T: Copy + Default>(buffer: u32) -> Vec<T> {
+        unsafe {
+            // The copy read target is used so the element buffer binding of any bound vertex array stays untouched
+            gl::BindBuffer(gl::COPY_READ_BUFFER, buffer);
+
+            let mut size = 0;
+            gl::GetBufferParameteriv(gl::COPY_READ_BUFFER, gl::BUFFER_SIZE, &mut size);
+
+            let mut data = vec![T::default(); size as usize / mem::size_of::<T>()];
+            gl::GetBufferSubData(
+                gl::COPY_READ_BUFFER,
+                0,
+                (data.len() * mem::size_of::<T>()) as GLsizeiptr,
+                data.as_mut_ptr() as *mut GLvoid,
+            );
+
+            gl::BindBuffer(gl::COPY_READ_BUFFER, 0);
+
+            data
+        }
+    }
+
     /// Binds the mesh for rendering.
     pub fn bind(&self) {
         unsafe {
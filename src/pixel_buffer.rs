@@ -0,0 +1,238 @@
+extern crate gl;
+
+use gl::types::*;
+use std::cell::Cell;
+use std::ptr;
+
+use crate::Texture;
+
+/// A pixel buffer used to upload pixels into textures asynchronously. The pixels are copied into a buffer owned by
+/// the driver and transferred to the texture in the background, so the CPU does not have to wait for the GPU.
+/// This is useful for data which changes every frame, such as video frames.
+pub struct PixelUploadBuffer {
+    buffer_id: GLuint,
+    capacity: Cell<usize>,
+}
+
+impl PixelUploadBuffer {
+    /// Creates a new pixel upload buffer. The buffer grows to fit the largest region uploaded through it.
+    pub fn new() -> Self {
+        let mut buffer_id = 0;
+
+        unsafe {
+            gl::GenBuffers(1, &mut buffer_id);
+        }
+
+        PixelUploadBuffer {
+            buffer_id,
+            capacity: Cell::new(0),
+        }
+    }
+
+    /// Writes pixels into a region of a texture, starting at the bottom left corner `(x, y)`.
+    /// The data has the same layout as for [`Texture::update_region`].
+    pub fn update_region(&self, texture: &Texture, x: u32, y: u32, width: u32, height: u32, data: &[u8]) -> Result<(), String> {
+        texture.check_region(x, y, width, height, data.len())?;
+
+        unsafe {
+            gl::BindBuffer(gl::PIXEL_UNPACK_BUFFER, self.buffer_id);
+
+            if data.len() > self.capacity.get() {
+                gl::BufferData(
+                    gl::PIXEL_UNPACK_BUFFER,
+                    data.len() as GLsizeiptr,
+                    ptr::null(),
+                    gl::STREAM_DRAW,
+                );
+                self.capacity.set(data.len());
+            }
+
+            // Invalidating the buffer lets the driver keep the previous upload alive without waiting for it
+            let mapped = gl::MapBufferRange(
+                gl::PIXEL_UNPACK_BUFFER,
+                0,
+                data.len() as GLsizeiptr,
+                gl::MAP_WRITE_BIT | gl::MAP_INVALIDATE_BUFFER_BIT,
+            ) as *mut u8;
+
+            if mapped.is_null() {
+                gl::BindBuffer(gl::PIXEL_UNPACK_BUFFER, 0);
+                return Err("[FerrousGl Error] Failed to map pixel upload buffer".to_string());
+            }
+
+            ptr::copy_nonoverlapping(data.as_ptr(), mapped, data.len());
+            gl::UnmapBuffer(gl::PIXEL_UNPACK_BUFFER);
+
+            // With a pixel unpack buffer bound, the pixel pointer is an offset into the buffer
+            texture.upload_region(x, y, width, height, ptr::null());
+
+            gl::BindBuffer(gl::PIXEL_UNPACK_BUFFER, 0);
+        }
+
+        Ok(())
+    }
+}
+
+/// A pixel buffer used to read pixels back from textures asynchronously. Reading is started with
+/// [`PixelReadbackBuffer::begin_read`] and the pixels can be fetched a few frames later once the GPU has
+/// finished, so screenshots and captures do not stall rendering.
+pub struct PixelReadbackBuffer {
+    buffer_id: GLuint,
+    fence: Option<GLsync>,
+    length: usize,
+    capacity: usize,
+}
+
+impl PixelReadbackBuffer {
+    /// Creates a new pixel readback buffer. The buffer grows to fit the largest region read through it.
+    pub fn new() -> Self {
+        let mut buffer_id = 0;
+
+        unsafe {
+            gl::GenBuffers(1, &mut buffer_id);
+        }
+
+        PixelReadbackBuffer {
+            buffer_id,
+            fence: None,
+            length: 0,
+            capacity: 0,
+        }
+    }
+
+    /// Starts reading a region of a texture, starting at the bottom left corner `(x, y)`. The pixels are returned in the
+    /// same layout as [`Texture::read_region_data`]. A read that is still pending is discarded.
+    pub fn begin_read(&mut self, texture: &Texture, x: u32, y: u32, width: u32, height: u32) -> Result<(), String> {
+        self.discard();
+
+        texture.check_bounds(x, y, width, height)?;
+        let length = texture.region_length(width, height);
+
+        unsafe {
+            gl::BindBuffer(gl::PIXEL_PACK_BUFFER, self.buffer_id);
+            if length > self.capacity {
+                gl::BufferData(gl::PIXEL_PACK_BUFFER, length as GLsizeiptr, ptr::null(), gl::STREAM_READ);
+                self.capacity = length;
+            }
+
+            // With a pixel pack buffer bound, the pixel pointer is an offset into the buffer
+            let result = texture.read_region_into(
+                x,
+                y,
+                width,
+                height,
+                texture.format.pixel_format(),
+                texture.format.pixel_type(),
+                ptr::null_mut(),
+            );
+
+            gl::BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
+            result?;
+
+            self.fence = Some(gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0));
+            // The fence is only guaranteed to signal once it has been sent to the GPU, which polling does not do
+            gl::Flush();
+        }
+
+        self.length = length;
+        Ok(())
+    }
+
+    /// Returns if a read has been started and the GPU has finished it.
+    pub fn is_ready(&self) -> bool {
+        match self.fence {
+            Some(fence) => unsafe {
+                let status = gl::ClientWaitSync(fence, 0, 0);
+                status == gl::ALREADY_SIGNALED || status == gl::CONDITION_SATISFIED
+            },
+            None => false,
+        }
+    }
+
+    /// Returns the pixels of the last started read if the GPU has finished it, without waiting.
+    pub fn try_read(&mut self) -> Option<Vec<u8>> {
+        if self.is_ready() {
+            self.read()
+        } else {
+            None
+        }
+    }
+
+    /// Waits for the GPU to finish the last started read and returns its pixels.
+    /// Returns `None` if no read was started.
+    pub fn wait(&mut self) -> Option<Vec<u8>> {
+        let fence = self.fence?;
+
+        unsafe {
+            while gl::ClientWaitSync(fence, gl::SYNC_FLUSH_COMMANDS_BIT, 1_000_000) == gl::TIMEOUT_EXPIRED {}
+        }
+
+        self.read()
+    }
+
+    /// Internal function to copy the finished read out of the buffer.
+    fn read(&mut self) -> Option<Vec<u8>> {
+        self.discard();
+
+        let mut data = vec![0u8; self.length];
+
+        unsafe {
+            gl::BindBuffer(gl::PIXEL_PACK_BUFFER, self.buffer_id);
+            let mapped = gl::MapBufferRange(gl::PIXEL_PACK_BUFFER, 0, self.length as GLsizeiptr, gl::MAP_READ_BIT) as *const u8;
+
+            if !mapped.is_null() {
+                ptr::copy_nonoverlapping(mapped, data.as_mut_ptr(), self.length);
+                gl::UnmapBuffer(gl::PIXEL_PACK_BUFFER);
+            }
+
+            gl::BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
+
+            if mapped.is_null() {
+                return None;
+            }
+        }
+
+        Some(data)
+    }
+
+    /// Internal function to delete the fence of a pending read.
+    fn discard(&mut self) {
+        if let Some(fence) = self.fence.take() {
+            unsafe {
+                gl::DeleteSync(fence);
+            }
+        }
+    }
+}
+
+impl Default for PixelReadbackBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for PixelReadbackBuffer {
+    /// Cleans up the buffer and pending fence when it goes out of scope.
+    fn drop(&mut self) {
+        self.discard();
+
+        unsafe {
+            gl::DeleteBuffers(1, &self.buffer_id);
+        }
+    }
+}
+
+impl Default for PixelUploadBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for PixelUploadBuffer {
+    /// Cleans up the buffer when it goes out of scope.
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.buffer_id);
+        }
+    }
+}
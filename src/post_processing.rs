@@ -0,0 +1,617 @@
+extern crate gl;
+
+use glam::{Mat4, Vec2, Vec3, Vec4};
+
+use crate::fullscreen::{FullscreenTriangle, PASSTHROUGH_FRAGMENT_SHADER};
+use crate::render_texture::RenderTarget;
+use crate::tonemapping::{framebuffer_encodes_srgb, TONEMAPPING_FRAGMENT_SHADER};
+use crate::{GlWindow, RenderTexture, Shader, Texture, TextureFormat, TonemapOperator};
+
+/// The largest amount of texels sampled on each side of a pixel by the gaussian blur.
+const MAX_BLUR_RADIUS: i32 = 32;
+
+/// Enum representing a value of a shader uniform, which post-processing passes set before drawing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UniformValue {
+    Int(i32),
+    Float(f32),
+    Vec2(Vec2),
+    Vec3(Vec3),
+    Vec4(Vec4),
+    Mat4(Mat4),
+}
+
+impl UniformValue {
+    /// Internal function to set the value as a uniform of the bound shader.
+    fn apply(&self, shader: &Shader, name: &str) {
+        match *self {
+            UniformValue::Int(value) => shader.set_uniform_1i(name, value),
+            UniformValue::Float(value) => shader.set_uniform_1f(name, value),
+            UniformValue::Vec2(value) => shader.set_uniform_2f(name, value.x, value.y),
+            UniformValue::Vec3(value) => shader.set_uniform_3f(name, value.x, value.y, value.z),
+            UniformValue::Vec4(value) => shader.set_uniform_4f(name, value.x, value.y, value.z, value.w),
+            UniformValue::Mat4(value) => shader.set_uniform_matrix_4fv(name, value.to_cols_array().as_ref()),
+        }
+    }
+}
+
+/// Enum representing the effect a post-processing pass applies to the image.
+pub enum PostEffect {
+    /// Adds a blurred copy of the parts brighter than `threshold` onto the image, making bright lights glow. Works best
+    /// on HDR images before tonemapping. The blur is done at half resolution with a standard deviation of `sigma` pixels.
+    Bloom { threshold: f32, intensity: f32, sigma: f32 },
+    /// Blurs the image with a separable gaussian blur with a standard deviation of `sigma` pixels.
+    GaussianBlur { sigma: f32 },
+    /// Fast approximate anti-aliasing, which smooths visible edges. Expects colors in the range `[0, 1]`, so it should
+    /// run after tonemapping.
+    Fxaa,
+    /// Darkens the image towards the corners. `radius` is the distance from the center at which darkening starts,
+    /// with 1.0 being the corners, `softness` the distance over which it fades in.
+    Vignette { intensity: f32, radius: f32, softness: f32 },
+    /// Maps colors through a lookup table texture. The texture is a horizontal strip of N tiles of N×N pixels, with red
+    /// increasing to the right within a tile, green increasing towards the top of the image and blue increasing from
+    /// tile to tile, like the common neutral LUT images. `intensity` blends between the original and graded colors.
+    ColorGrading { lut: Texture, intensity: f32 },
+    /// Separates the color channels towards the edges of the image like a cheap lens. `strength` is the offset of the
+    /// red and blue channel at the edges, as a fraction of the image size.
+    ChromaticAberration { strength: f32 },
+    /// Maps HDR colors into the displayable range, like a [`crate::Tonemapper`].
+    Tonemapping { operator: TonemapOperator, exposure: f32 },
+    /// A user shader, see [`PostProcessPass::custom`].
+    Custom(Shader),
+}
+
+/// A pass of a [`PostProcessChain`], which applies an effect and optionally sets additional uniforms on its shader.
+pub struct PostProcessPass {
+    effect: PostEffect,
+    uniforms: Vec<(String, UniformValue)>,
+    enabled: bool,
+}
+
+impl PostProcessPass {
+    /// Creates a new enabled pass applying the given effect.
+    pub fn new(effect: PostEffect) -> Self {
+        PostProcessPass {
+            effect,
+            uniforms: Vec::new(),
+            enabled: true,
+        }
+    }
+
+    /// Creates a pass from the source of a fragment shader. The shader receives the image as `sampler2D sourceTexture`,
+    /// the size of one of its texels as `vec2 texelSize` and the texture coordinate as `in vec2 TexCoord`, and writes
+    /// its result to its single output.
+    pub fn custom(fragment_source: &str) -> Self {
+        Self::new(PostEffect::Custom(Shader::new_fullscreen_from_source(fragment_source)))
+    }
+
+    /// Sets a uniform that is set on the shader of the pass before drawing, replacing a previous value with the same
+    /// name. Uniforms of the built-in effects can be overridden this way as well.
+    pub fn set_uniform(&mut self, name: &str, value: UniformValue) {
+        match self.uniforms.iter_mut().find(|(existing, _)| existing == name) {
+            Some((_, existing)) => *existing = value,
+            None => self.uniforms.push((name.to_string(), value)),
+        }
+    }
+
+    /// Removes a uniform set with [`PostProcessPass::set_uniform`].
+    pub fn remove_uniform(&mut self, name: &str) {
+        self.uniforms.retain(|(existing, _)| existing != name);
+    }
+
+    /// Enables or disables the pass. Disabled passes are skipped.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Returns if the pass is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Returns the effect of the pass.
+    pub fn effect(&self) -> &PostEffect {
+        &self.effect
+    }
+
+    /// Returns the effect of the pass mutably, which allows changing its parameters.
+    pub fn effect_mut(&mut self) -> &mut PostEffect {
+        &mut self.effect
+    }
+
+    /// Internal function to set the additional uniforms on the bound shader.
+    fn apply_uniforms(&self, shader: &Shader) {
+        for (name, value) in &self.uniforms {
+            value.apply(shader, name);
+        }
+    }
+}
+
+/// Internal struct holding the shaders of the built-in effects.
+struct BuiltinShaders {
+    passthrough: Shader,
+    bright_pass: Shader,
+    blur: Shader,
+    bloom_composite: Shader,
+    fxaa: Shader,
+    vignette: Shader,
+    color_grading: Shader,
+    chromatic_aberration: Shader,
+    tonemapping: Shader,
+}
+
+impl BuiltinShaders {
+    fn new() -> Self {
+        let compile = Shader::new_fullscreen_from_source;
+
+        BuiltinShaders {
+            passthrough: compile(PASSTHROUGH_FRAGMENT_SHADER),
+            bright_pass: compile(BRIGHT_PASS_FRAGMENT_SHADER),
+            blur: compile(BLUR_FRAGMENT_SHADER),
+            bloom_composite: compile(BLOOM_COMPOSITE_FRAGMENT_SHADER),
+            fxaa: compile(FXAA_FRAGMENT_SHADER),
+            vignette: compile(VIGNETTE_FRAGMENT_SHADER),
+            color_grading: compile(COLOR_GRADING_FRAGMENT_SHADER),
+            chromatic_aberration: compile(CHROMATIC_ABERRATION_FRAGMENT_SHADER),
+            tonemapping: compile(TONEMAPPING_FRAGMENT_SHADER),
+        }
+    }
+}
+
+/// Runs an ordered list of fullscreen passes over a rendered scene. The scene is rendered into
+/// [`PostProcessChain::scene_target`], after which [`PostProcessChain::render`] applies every enabled pass, ping-ponging
+/// between two render targets of the window size, and draws the result onto the current bound framebuffer.
+pub struct PostProcessChain {
+    targets: [RenderTexture; 2],
+    bloom_targets: [RenderTexture; 2],
+    passes: Vec<PostProcessPass>,
+    shaders: BuiltinShaders,
+    triangle: FullscreenTriangle,
+}
+
+impl PostProcessChain {
+    /// Creates a new chain without passes, with render targets of the windows framebuffer size and the given color
+    /// format, for example [`TextureFormat::Rgba16F`] for HDR scenes. The scene target also has a depth attachment.
+    pub fn new(window: &GlWindow, format: TextureFormat) -> Result<Self, String> {
+        let (width, height) = window.get_framebuffer_size();
+        let (width, height) = (width.max(1) as u32, height.max(1) as u32);
+        let (half_width, half_height) = (width.div_ceil(2), height.div_ceil(2));
+
+        let target = |width, height, scale, with_depth| {
+            RenderTexture::builder(width, height)
+                .color_attachment(format)
+                .with_depth(with_depth)
+                .track_window_size(scale)
+                .build()
+        };
+
+        Ok(PostProcessChain {
+            targets: [target(width, height, 1.0, true)?, target(width, height, 1.0, false)?],
+            bloom_targets: [
+                target(half_width, half_height, 0.5, false)?,
+                target(half_width, half_height, 0.5, false)?,
+            ],
+            passes: Vec::new(),
+            shaders: BuiltinShaders::new(),
+            triangle: FullscreenTriangle::new(),
+        })
+    }
+
+    /// Adds a pass at the end of the chain and returns its index.
+    pub fn add_pass(&mut self, pass: PostProcessPass) -> usize {
+        self.passes.push(pass);
+        self.passes.len() - 1
+    }
+
+    /// Inserts a pass at the given index, moving all passes after it back by one.
+    pub fn insert_pass(&mut self, index: usize, pass: PostProcessPass) -> Result<(), String> {
+        if index > self.passes.len() {
+            return Err(format!(
+                "[FerrousGl Error] Can not insert a pass at index {} of a chain with {} passes",
+                index,
+                self.passes.len()
+            ));
+        }
+
+        self.passes.insert(index, pass);
+        Ok(())
+    }
+
+    /// Removes the pass at the given index and returns it.
+    pub fn remove_pass(&mut self, index: usize) -> Option<PostProcessPass> {
+        (index < self.passes.len()).then(|| self.passes.remove(index))
+    }
+
+    /// Returns the pass at the given index.
+    pub fn pass(&self, index: usize) -> Option<&PostProcessPass> {
+        self.passes.get(index)
+    }
+
+    /// Returns the pass at the given index mutably, which allows changing its effect, uniforms or enabling it.
+    pub fn pass_mut(&mut self, index: usize) -> Option<&mut PostProcessPass> {
+        self.passes.get_mut(index)
+    }
+
+    /// Returns the amount of passes, including disabled ones.
+    pub fn pass_count(&self) -> usize {
+        self.passes.len()
+    }
+
+    /// Returns the render texture the scene has to be rendered into before calling [`PostProcessChain::render`].
+    pub fn scene_target(&self) -> &RenderTexture {
+        &self.targets[0]
+    }
+
+    /// Resizes the render targets to the windows framebuffer size if it changed. Call this once per frame before
+    /// rendering the scene. Returns if the render targets were resized.
+    pub fn track_window(&mut self, window: &GlWindow) -> Result<bool, String> {
+        let mut resized = false;
+        for target in self.targets.iter_mut().chain(self.bloom_targets.iter_mut()) {
+            resized |= target.track_window(window)?;
+        }
+
+        Ok(resized)
+    }
+
+    /// Applies all enabled passes to the scene target and draws the result onto the whole viewport of the current
+    /// bound framebuffer, usually the window. Depth testing and blending are disabled while drawing.
+    pub fn render(&self) {
+        let output = RenderTarget::current();
+        let (depth_test, blend) = unsafe { (gl::IsEnabled(gl::DEPTH_TEST), gl::IsEnabled(gl::BLEND)) };
+        unsafe {
+            gl::Disable(gl::DEPTH_TEST);
+            gl::Disable(gl::BLEND);
+        }
+
+        let passes = self.passes.iter().filter(|pass| pass.enabled).collect::<Vec<_>>();
+        let effects = passes.iter().map(|pass| &pass.effect).collect::<Vec<_>>();
+        // If passes follow tonemapping, all passes render into the targets and a final copy encodes the image to sRGB
+        let output_encodes_srgb = framebuffer_encodes_srgb();
+        let encode_srgb = needs_final_srgb_encode(&effects, output_encodes_srgb);
+
+        // Index of the target holding the current image
+        let mut current = 0;
+        for (index, pass) in passes.iter().enumerate() {
+            let last = index == passes.len() - 1 && !encode_srgb;
+            let next = 1 - current;
+            let source = self.targets[current].texture();
+            let destination = (!last).then(|| &self.targets[next]);
+
+            match pass.effect {
+                PostEffect::GaussianBlur { sigma } => {
+                    // The horizontal blur goes into the other target, the vertical one back into this target
+                    self.blur(pass, source, Some(&self.targets[next]), output, sigma, Vec2::X);
+                    let destination = (!last).then(|| &self.targets[current]);
+                    self.blur(pass, self.targets[next].texture(), destination, output, sigma, Vec2::Y);
+                    continue;
+                }
+                PostEffect::Bloom { threshold, intensity, sigma } => {
+                    let shader = &self.shaders.bright_pass;
+                    self.draw(shader, source, Some(&self.bloom_targets[0]), output, |shader| {
+                        shader.set_uniform_1f("threshold", threshold);
+                        pass.apply_uniforms(shader);
+                    });
+
+                    // The bloom targets have half the resolution, so the blur is half as wide in their texels
+                    let bloom = self.bloom_targets[0].texture();
+                    let (horizontal, vertical) = (Some(&self.bloom_targets[1]), Some(&self.bloom_targets[0]));
+                    self.blur(pass, bloom, horizontal, output, sigma * 0.5, Vec2::X);
+                    self.blur(pass, self.bloom_targets[1].texture(), vertical, output, sigma * 0.5, Vec2::Y);
+
+                    let shader = &self.shaders.bloom_composite;
+                    self.draw(shader, source, destination, output, |shader| {
+                        bloom.bind(1);
+                        shader.set_uniform_texture("bloomTexture", 1);
+                        shader.set_uniform_1f("intensity", intensity);
+                        pass.apply_uniforms(shader);
+                    });
+                }
+                PostEffect::Fxaa => {
+                    self.draw(&self.shaders.fxaa, source, destination, output, |shader| pass.apply_uniforms(shader));
+                }
+                PostEffect::Vignette { intensity, radius, softness } => {
+                    self.draw(&self.shaders.vignette, source, destination, output, |shader| {
+                        shader.set_uniform_1f("intensity", intensity);
+                        shader.set_uniform_1f("radius", radius);
+                        shader.set_uniform_1f("softness", softness);
+                        pass.apply_uniforms(shader);
+                    });
+                }
+                PostEffect::ColorGrading { ref lut, intensity } => {
+                    self.draw(&self.shaders.color_grading, source, destination, output, |shader| {
+                        lut.bind(1);
+                        shader.set_uniform_texture("lutTexture", 1);
+                        shader.set_uniform_1f("intensity", intensity);
+                        pass.apply_uniforms(shader);
+                    });
+                }
+                PostEffect::ChromaticAberration { strength } => {
+                    self.draw(&self.shaders.chromatic_aberration, source, destination, output, |shader| {
+                        shader.set_uniform_1f("strength", strength);
+                        pass.apply_uniforms(shader);
+                    });
+                }
+                PostEffect::Tonemapping { operator, exposure } => {
+                    self.draw(&self.shaders.tonemapping, source, destination, output, |shader| {
+                        shader.set_uniform_texture("hdrTexture", 0);
+                        shader.set_uniform_1i("operator", operator.shader_index());
+                        shader.set_uniform_1f("exposure", exposure);
+                        if let TonemapOperator::ReinhardExtended { white_point } = operator {
+                            shader.set_uniform_1f("whitePoint", white_point);
+                        }
+                        // Only the final image is encoded to sRGB, the render targets hold linear colors
+                        shader.set_uniform_1i("encodeSrgb", (last && !output_encodes_srgb) as i32);
+                        pass.apply_uniforms(shader);
+                    });
+                }
+                PostEffect::Custom(ref shader) => {
+                    self.draw(shader, source, destination, output, |shader| pass.apply_uniforms(shader));
+                }
+            }
+
+            current = next;
+        }
+
+        if passes.is_empty() || encode_srgb {
+            self.draw(&self.shaders.passthrough, self.targets[current].texture(), None, output, |shader| {
+                shader.set_uniform_2f("uvOffset", 0.0, 0.0);
+                shader.set_uniform_2f("uvScale", 1.0, 1.0);
+                shader.set_uniform_1i("encodeSrgb", encode_srgb as i32);
+            });
+        }
+
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE1);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+            gl::UseProgram(0);
+
+            if depth_test == gl::TRUE {
+                gl::Enable(gl::DEPTH_TEST);
+            }
+            if blend == gl::TRUE {
+                gl::Enable(gl::BLEND);
+            }
+        }
+    }
+
+    /// Internal function drawing one direction of the gaussian blur.
+    fn blur(
+        &self,
+        pass: &PostProcessPass,
+        source: &Texture,
+        destination: Option<&RenderTexture>,
+        output: RenderTarget,
+        sigma: f32,
+        axis: Vec2,
+    ) {
+        let sigma = sigma.max(0.01);
+        let radius = ((sigma * 3.0).ceil() as i32).clamp(1, MAX_BLUR_RADIUS);
+
+        self.draw(&self.shaders.blur, source, destination, output, |shader| {
+            let direction = axis / Vec2::new(source.width() as f32, source.height() as f32);
+            shader.set_uniform_2f("direction", direction.x, direction.y);
+            shader.set_uniform_1f("sigma", sigma);
+            shader.set_uniform_1i("radius", radius);
+            pass.apply_uniforms(shader);
+        });
+    }
+
+    /// Internal function drawing a fullscreen pass from a source texture into a render target, or into the output
+    /// framebuffer if there is none.
+    fn draw(
+        &self,
+        shader: &Shader,
+        source: &Texture,
+        destination: Option<&RenderTexture>,
+        output: RenderTarget,
+        set_uniforms: impl FnOnce(&Shader),
+    ) {
+        let _guard = destination.map(RenderTexture::bind_scoped);
+        if destination.is_none() {
+            output.restore();
+        }
+
+        shader.bind_program();
+        source.bind(0);
+        shader.set_uniform_texture("sourceTexture", 0);
+        shader.set_uniform_2f("texelSize", 1.0 / source.width() as f32, 1.0 / source.height() as f32);
+        set_uniforms(shader);
+
+        self.triangle.draw();
+    }
+}
+
+/// Internal function returning if the image needs a final copy encoding it to sRGB, which is the case if passes follow
+/// a tonemapping pass and the output framebuffer does not encode sRGB itself. A tonemapping pass at the end encodes
+/// the image by itself.
+fn needs_final_srgb_encode(effects: &[&PostEffect], output_encodes_srgb: bool) -> bool {
+    let tonemapping = effects.iter().position(|effect| matches!(effect, PostEffect::Tonemapping { .. }));
+    !output_encodes_srgb && tonemapping.is_some_and(|index| index + 1 < effects.len())
+}
+
+const BRIGHT_PASS_FRAGMENT_SHADER: &str = r#"#version 330 core
+in vec2 TexCoord;
+out vec4 FragColor;
+
+uniform sampler2D sourceTexture;
+uniform float threshold;
+
+void main() {
+    vec3 color = texture(sourceTexture, TexCoord).rgb;
+    float brightness = max(color.r, max(color.g, color.b));
+    float contribution = max(brightness - threshold, 0.0) / max(brightness, 0.0001);
+    FragColor = vec4(color * contribution, 1.0);
+}
+"#;
+
+const BLUR_FRAGMENT_SHADER: &str = r#"#version 330 core
+in vec2 TexCoord;
+out vec4 FragColor;
+
+uniform sampler2D sourceTexture;
+uniform vec2 direction;
+uniform float sigma;
+uniform int radius;
+
+void main() {
+    vec4 center = texture(sourceTexture, TexCoord);
+    vec3 sum = center.rgb;
+    float weightSum = 1.0;
+
+    for (int i = 1; i <= radius; i++) {
+        float weight = exp(-float(i * i) / (2.0 * sigma * sigma));
+        sum += texture(sourceTexture, TexCoord + direction * float(i)).rgb * weight;
+        sum += texture(sourceTexture, TexCoord - direction * float(i)).rgb * weight;
+        weightSum += 2.0 * weight;
+    }
+
+    FragColor = vec4(sum / weightSum, center.a);
+}
+"#;
+
+const BLOOM_COMPOSITE_FRAGMENT_SHADER: &str = r#"#version 330 core
+in vec2 TexCoord;
+out vec4 FragColor;
+
+uniform sampler2D sourceTexture;
+uniform sampler2D bloomTexture;
+uniform float intensity;
+
+void main() {
+    vec4 color = texture(sourceTexture, TexCoord);
+    FragColor = vec4(color.rgb + texture(bloomTexture, TexCoord).rgb * intensity, color.a);
+}
+"#;
+
+const FXAA_FRAGMENT_SHADER: &str = r#"#version 330 core
+in vec2 TexCoord;
+out vec4 FragColor;
+
+uniform sampler2D sourceTexture;
+uniform vec2 texelSize;
+
+const float REDUCE_MIN = 1.0 / 128.0;
+const float REDUCE_MUL = 1.0 / 8.0;
+const float SPAN_MAX = 8.0;
+
+float luma(vec3 color) {
+    return dot(color, vec3(0.299, 0.587, 0.114));
+}
+
+void main() {
+    vec4 center = texture(sourceTexture, TexCoord);
+    float lumaNW = luma(texture(sourceTexture, TexCoord + vec2(-1.0, -1.0) * texelSize).rgb);
+    float lumaNE = luma(texture(sourceTexture, TexCoord + vec2(1.0, -1.0) * texelSize).rgb);
+    float lumaSW = luma(texture(sourceTexture, TexCoord + vec2(-1.0, 1.0) * texelSize).rgb);
+    float lumaSE = luma(texture(sourceTexture, TexCoord + vec2(1.0, 1.0) * texelSize).rgb);
+    float lumaM = luma(center.rgb);
+
+    float lumaMin = min(lumaM, min(min(lumaNW, lumaNE), min(lumaSW, lumaSE)));
+    float lumaMax = max(lumaM, max(max(lumaNW, lumaNE), max(lumaSW, lumaSE)));
+
+    vec2 direction = vec2(-((lumaNW + lumaNE) - (lumaSW + lumaSE)), (lumaNW + lumaSW) - (lumaNE + lumaSE));
+    float directionReduce = max((lumaNW + lumaNE + lumaSW + lumaSE) * 0.25 * REDUCE_MUL, REDUCE_MIN);
+    float inverseDirectionMin = 1.0 / (min(abs(direction.x), abs(direction.y)) + directionReduce);
+    direction = clamp(direction * inverseDirectionMin, vec2(-SPAN_MAX), vec2(SPAN_MAX)) * texelSize;
+
+    vec3 colorA = 0.5 * (
+        texture(sourceTexture, TexCoord + direction * (1.0 / 3.0 - 0.5)).rgb +
+        texture(sourceTexture, TexCoord + direction * (2.0 / 3.0 - 0.5)).rgb);
+    vec3 colorB = colorA * 0.5 + 0.25 * (
+        texture(sourceTexture, TexCoord - direction * 0.5).rgb +
+        texture(sourceTexture, TexCoord + direction * 0.5).rgb);
+
+    float lumaB = luma(colorB);
+    FragColor = vec4((lumaB < lumaMin || lumaB > lumaMax) ? colorA : colorB, center.a);
+}
+"#;
+
+const VIGNETTE_FRAGMENT_SHADER: &str = r#"#version 330 core
+in vec2 TexCoord;
+out vec4 FragColor;
+
+uniform sampler2D sourceTexture;
+uniform float intensity;
+uniform float radius;
+uniform float softness;
+
+void main() {
+    vec4 color = texture(sourceTexture, TexCoord);
+    // 1.0 at the corners
+    float distance = length(TexCoord - 0.5) * 1.41421356;
+    float darkening = smoothstep(radius, radius + max(softness, 0.0001), distance);
+    FragColor = vec4(color.rgb * (1.0 - intensity * darkening), color.a);
+}
+"#;
+
+const COLOR_GRADING_FRAGMENT_SHADER: &str = r#"#version 330 core
+in vec2 TexCoord;
+out vec4 FragColor;
+
+uniform sampler2D sourceTexture;
+uniform sampler2D lutTexture;
+uniform float intensity;
+
+vec3 sampleLut(vec3 color) {
+    float size = float(textureSize(lutTexture, 0).y);
+    float blue = color.b * (size - 1.0);
+    float slice0 = floor(blue);
+    float slice1 = min(slice0 + 1.0, size - 1.0);
+
+    // Texel centers of red and green within a tile, image rows are stored from the top
+    float x = (color.r * (size - 1.0) + 0.5) / (size * size);
+    float y = 1.0 - (color.g * (size - 1.0) + 0.5) / size;
+
+    vec3 color0 = texture(lutTexture, vec2(x + slice0 / size, y)).rgb;
+    vec3 color1 = texture(lutTexture, vec2(x + slice1 / size, y)).rgb;
+    return mix(color0, color1, blue - slice0);
+}
+
+void main() {
+    vec4 color = texture(sourceTexture, TexCoord);
+    vec3 graded = sampleLut(clamp(color.rgb, 0.0, 1.0));
+    FragColor = vec4(mix(color.rgb, graded, intensity), color.a);
+}
+"#;
+
+const CHROMATIC_ABERRATION_FRAGMENT_SHADER: &str = r#"#version 330 core
+in vec2 TexCoord;
+out vec4 FragColor;
+
+uniform sampler2D sourceTexture;
+uniform float strength;
+
+void main() {
+    vec2 offset = (TexCoord - 0.5) * 2.0 * strength;
+    vec4 color = texture(sourceTexture, TexCoord);
+    float red = texture(sourceTexture, TexCoord - offset).r;
+    float blue = texture(sourceTexture, TexCoord + offset).b;
+    FragColor = vec4(red, color.g, blue, color.a);
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tonemapping() -> PostEffect {
+        PostEffect::Tonemapping { operator: TonemapOperator::AcesFilmic, exposure: 1.0 }
+    }
+
+    #[test]
+    fn tonemapping_followed_by_fxaa_needs_final_encode() {
+        let (tonemapping, fxaa) = (tonemapping(), PostEffect::Fxaa);
+        assert!(needs_final_srgb_encode(&[&tonemapping, &fxaa], false));
+        assert!(!needs_final_srgb_encode(&[&tonemapping, &fxaa], true));
+    }
+
+    #[test]
+    fn tonemapping_at_the_end_encodes_by_itself() {
+        let (fxaa, tonemapping) = (PostEffect::Fxaa, tonemapping());
+        assert!(!needs_final_srgb_encode(&[&fxaa, &tonemapping], false));
+        assert!(!needs_final_srgb_encode(&[&fxaa], false));
+        assert!(!needs_final_srgb_encode(&[], false));
+    }
+}
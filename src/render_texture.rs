@@ -1,12 +1,34 @@
-use gl::types::GLuint;
+use std::cell::{Cell, OnceCell};
 
-use crate::Texture;
+use gl::types::{GLenum, GLuint};
+use glam::Vec4;
+
+use crate::blit::{blit, default_framebuffer_samples, BlitMask, Rect, ScalingMode};
+use crate::{FilterMode, GlWindow, Sampler, SamplerSettings, Texture, Texture3D, TextureArray, TextureFormat, WrapMode};
 
 /// Represents a render texture, which allows rendering on. Can be used like a [`ferrousgl::texture::Texture`].
+/// A render texture can have multiple color textures (attachments) which fragment shaders write to at the same time,
+/// for example the G-buffer of a deferred renderer, see [`RenderTextureBuilder`].
+///
+/// Multisampled render textures render into multisampled renderbuffers, which are resolved into the (single sampled)
+/// textures of the render texture by [`RenderTexture::resolve`].
 pub struct RenderTexture {
     framebuffer_id: GLuint,
-    texture: Texture,
-    depth_texture: Option<Texture>, // Added depth texture
+    resolve_framebuffer_id: GLuint,
+    color_formats: Vec<TextureFormat>,
+    color_textures: Vec<Texture>,
+    color_renderbuffer_ids: Vec<GLuint>,
+    clear_colors: Vec<Vec4>,
+    depth_attachment: DepthAttachment,
+    depth_texture: Option<Texture>,
+    depth_renderbuffer_id: GLuint,
+    clear_depth: f32,
+    clear_stencil: i32,
+    samples: u32,
+    window_scale: Option<f32>,
+    previous_target: Cell<Option<RenderTarget>>,
+    /// Samplers with nearest and linear filtering for presenting onto multisampled windows, created on first use.
+    present_samplers: [OnceCell<Sampler>; 2],
     width: u32,
     height: u32,
 }
@@ -15,75 +37,32 @@ impl RenderTexture {
     /// Creates a new render texture with the specified width and height.
     /// Optionally creates a depth texture attachment if `with_depth` is true.
     pub fn new(width: u32, height: u32, with_depth: bool) -> Result<Self, String> {
-        let mut framebuffer_id = 0;
-        let texture = Texture::new_empty(width, height)?;
-
-        let depth_texture = if with_depth {
-            Some(Self::create_depth_texture(width, height)?)
-        } else {
-            None
-        };
-
-        unsafe {
-            gl::GenFramebuffers(1, &mut framebuffer_id);
-            gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer_id);
-
-            gl::FramebufferTexture2D(
-                gl::FRAMEBUFFER,
-                gl::COLOR_ATTACHMENT0,
-                gl::TEXTURE_2D,
-                texture.id,
-                0,
-            );
-
-            if let Some(ref depth) = depth_texture {
-                gl::FramebufferTexture2D(
-                    gl::FRAMEBUFFER,
-                    gl::DEPTH_ATTACHMENT,
-                    gl::TEXTURE_2D,
-                    depth.id,
-                    0,
-                );
-            }
-
-            if gl::CheckFramebufferStatus(gl::FRAMEBUFFER) != gl::FRAMEBUFFER_COMPLETE {
-                return Err("[FerrousGl Error] Framebuffer is not complete!".to_string());
-            }
+        Self::new_with_format(width, height, TextureFormat::Rgba8, with_depth)
+    }
 
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
-        }
+    /// Creates a new render texture with the specified width, height and color format, for example
+    /// [`TextureFormat::Rgba16F`] for rendering HDR colors which are tonemapped later with a [`crate::Tonemapper`].
+    /// Optionally creates a depth texture attachment if `with_depth` is true.
+    pub fn new_with_format(width: u32, height: u32, format: TextureFormat, with_depth: bool) -> Result<Self, String> {
+        RenderTextureBuilder::new(width, height)
+            .color_attachment(format)
+            .with_depth(with_depth)
+            .build()
+    }
 
-        Ok(RenderTexture {
-            framebuffer_id,
-            texture,
-            depth_texture,
-            width,
-            height,
-        })
+    /// Returns a builder for a render texture with the specified width and height, which allows
+    /// choosing the amount and formats of color attachments.
+    pub fn builder(width: u32, height: u32) -> RenderTextureBuilder {
+        RenderTextureBuilder::new(width, height)
     }
 
-    /// Creates a depth texture with the specified dimensions
-    fn create_depth_texture(width: u32, height: u32) -> Result<Texture, String> {
-        let mut texture_id = 0;
+    /// Creates a depth texture with the specified dimensions and depth format
+    fn create_depth_texture(width: u32, height: u32, format: TextureFormat) -> Result<Texture, String> {
+        let texture = Texture::new_empty_with_format(width, height, format)?;
 
         unsafe {
-            gl::GenTextures(1, &mut texture_id);
-            gl::BindTexture(gl::TEXTURE_2D, texture_id);
+            gl::BindTexture(gl::TEXTURE_2D, texture.id);
 
-            gl::TexImage2D(
-                gl::TEXTURE_2D,
-                0,
-                gl::DEPTH_COMPONENT as i32,
-                width as i32,
-                height as i32,
-                0,
-                gl::DEPTH_COMPONENT,
-                gl::UNSIGNED_INT,
-                std::ptr::null(),
-            );
-
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
             gl::TexParameteri(
                 gl::TEXTURE_2D,
                 gl::TEXTURE_WRAP_S,
@@ -105,44 +84,779 @@ impl RenderTexture {
             gl::BindTexture(gl::TEXTURE_2D, 0);
         }
 
-        Ok(Texture {
-            id: texture_id,
-            width,
-            height,
-        })
+        Ok(texture)
+    }
+
+    /// Creates a renderbuffer with the specified dimensions, format and amount of samples
+    fn create_renderbuffer(width: u32, height: u32, format: TextureFormat, samples: u32) -> GLuint {
+        let mut renderbuffer_id = 0;
+
+        unsafe {
+            gl::GenRenderbuffers(1, &mut renderbuffer_id);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, renderbuffer_id);
+            gl::RenderbufferStorageMultisample(
+                gl::RENDERBUFFER,
+                if samples > 1 { samples as i32 } else { 0 },
+                format.internal_format(),
+                width as i32,
+                height as i32,
+            );
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+        }
+
+        renderbuffer_id
+    }
+
+    /// Internal function to (re)create all textures and renderbuffers at the current size and attach them.
+    fn allocate(&mut self) -> Result<(), String> {
+        self.delete_renderbuffers();
+
+        let (width, height, samples) = (self.width, self.height, self.samples);
+
+        self.color_textures = self
+            .color_formats
+            .iter()
+            .map(|&format| Texture::new_empty_with_format(width, height, format))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        if self.is_multisampled() {
+            self.color_renderbuffer_ids = self
+                .color_formats
+                .iter()
+                .map(|&format| Self::create_renderbuffer(width, height, format, samples))
+                .collect();
+        }
+
+        (self.depth_texture, self.depth_renderbuffer_id) = match self.depth_attachment {
+            DepthAttachment::None => (None, 0),
+            DepthAttachment::Texture(format) => (
+                Some(Self::create_depth_texture(width, height, format)?),
+                // The multisampled framebuffer renders depth into a renderbuffer, which is resolved into the texture
+                if self.is_multisampled() { Self::create_renderbuffer(width, height, format, samples) } else { 0 },
+            ),
+            DepthAttachment::Renderbuffer(format) => (None, Self::create_renderbuffer(width, height, format, samples)),
+        };
+
+        let mut complete = with_framebuffer(self.framebuffer_id, || self.attach_all(false));
+        if self.is_multisampled() {
+            complete &= with_framebuffer(self.resolve_framebuffer_id, || self.attach_all(true));
+        }
+
+        if !complete {
+            return Err("[FerrousGl Error] Framebuffer is not complete!".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Internal function to attach all attachments to the bound framebuffer and set up its draw buffers.
+    /// Returns if the framebuffer is complete.
+    fn attach_all(&self, resolve: bool) -> bool {
+        let use_renderbuffers = self.is_multisampled() && !resolve;
+
+        unsafe {
+            for index in 0..self.color_formats.len() {
+                let attachment = gl::COLOR_ATTACHMENT0 + index as u32;
+                if use_renderbuffers {
+                    gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, attachment, gl::RENDERBUFFER, self.color_renderbuffer_ids[index]);
+                } else {
+                    gl::FramebufferTexture2D(gl::FRAMEBUFFER, attachment, gl::TEXTURE_2D, self.color_textures[index].id, 0);
+                }
+            }
+
+            self.attach_depth(resolve);
+
+            // Fragment shader output n is written to color attachment n
+            let draw_buffers = (0..self.color_formats.len() as u32)
+                .map(|index| gl::COLOR_ATTACHMENT0 + index)
+                .collect::<Vec<_>>();
+            if draw_buffers.is_empty() {
+                gl::DrawBuffer(gl::NONE);
+                gl::ReadBuffer(gl::NONE);
+            } else {
+                gl::DrawBuffers(draw_buffers.len() as i32, draw_buffers.as_ptr());
+            }
+
+            gl::CheckFramebufferStatus(gl::FRAMEBUFFER) == gl::FRAMEBUFFER_COMPLETE
+        }
     }
 
-    /// Binds the render texture as the current framebuffer.
+    /// Internal function to delete all renderbuffers.
+    fn delete_renderbuffers(&mut self) {
+        unsafe {
+            for renderbuffer_id in self.color_renderbuffer_ids.drain(..) {
+                gl::DeleteRenderbuffers(1, &renderbuffer_id);
+            }
+            if self.depth_renderbuffer_id != 0 {
+                gl::DeleteRenderbuffers(1, &self.depth_renderbuffer_id);
+                self.depth_renderbuffer_id = 0;
+            }
+        }
+    }
+
+    /// Resolves the samples of a multisampled render texture into its textures, after which they can be sampled or
+    /// saved. This has to be done after rendering and before using the textures. Does nothing if the render texture
+    /// is not multisampled.
+    pub fn resolve(&self) {
+        if !self.is_multisampled() {
+            return;
+        }
+
+        let (width, height) = (self.width as i32, self.height as i32);
+
+        unsafe {
+            let (mut previous_read_framebuffer, mut previous_draw_framebuffer) = (0, 0);
+            gl::GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut previous_read_framebuffer);
+            gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut previous_draw_framebuffer);
+
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.framebuffer_id);
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, self.resolve_framebuffer_id);
+
+            // A blit copies from one read buffer into all draw buffers, so each attachment is resolved on its own
+            let attachment_count = self.color_formats.len() as u32;
+            for index in 0..attachment_count {
+                let draw_buffers = (0..attachment_count)
+                    .map(|other| if other == index { gl::COLOR_ATTACHMENT0 + other } else { gl::NONE })
+                    .collect::<Vec<_>>();
+
+                gl::ReadBuffer(gl::COLOR_ATTACHMENT0 + index);
+                gl::DrawBuffers(draw_buffers.len() as i32, draw_buffers.as_ptr());
+                gl::BlitFramebuffer(0, 0, width, height, 0, 0, width, height, gl::COLOR_BUFFER_BIT, gl::NEAREST);
+            }
+
+            if let (Some(format), Some(_)) = (self.depth_format(), &self.depth_texture) {
+                let mask = if format.has_stencil() {
+                    gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT
+                } else {
+                    gl::DEPTH_BUFFER_BIT
+                };
+                gl::BlitFramebuffer(0, 0, width, height, 0, 0, width, height, mask, gl::NEAREST);
+            }
+
+            if attachment_count > 0 {
+                let draw_buffers = (0..attachment_count)
+                    .map(|index| gl::COLOR_ATTACHMENT0 + index)
+                    .collect::<Vec<_>>();
+                gl::ReadBuffer(gl::COLOR_ATTACHMENT0);
+                gl::DrawBuffers(draw_buffers.len() as i32, draw_buffers.as_ptr());
+            }
+
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, previous_read_framebuffer as GLuint);
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, previous_draw_framebuffer as GLuint);
+        }
+    }
+
+    /// Returns the amount of samples per pixel, 0 or 1 if the render texture is not multisampled.
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    /// Returns if the render texture is multisampled and has to be resolved with [`RenderTexture::resolve`].
+    pub fn is_multisampled(&self) -> bool {
+        self.samples > 1
+    }
+
+    /// Resizes the render texture, reallocating all of its textures and renderbuffers. Their contents are lost and
+    /// references to the old textures can not be held across a resize. Does nothing if the size did not change.
+    /// Texture array layers or 3D texture slices that were attached are replaced by the own attachments again.
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), String> {
+        if width == 0 || height == 0 {
+            return Err(format!("[FerrousGl Error] Can not resize a render texture to {}x{}", width, height));
+        }
+        if (width, height) == (self.width, self.height) {
+            return Ok(());
+        }
+
+        self.width = width;
+        self.height = height;
+        self.allocate()
+    }
+
+    /// Resizes the render texture to the windows framebuffer size multiplied by the scale set with
+    /// [`RenderTextureBuilder::track_window_size`] or [`RenderTexture::set_window_scale`]. Call this once per frame,
+    /// for example after [`GlWindow::update`]. Returns if the render texture was resized, in which case the textures
+    /// have to be bound again. Does nothing for render textures that do not track the window size, or while the
+    /// window is minimized.
+    pub fn track_window(&mut self, window: &GlWindow) -> Result<bool, String> {
+        let Some(scale) = self.window_scale else {
+            return Ok(false);
+        };
+
+        let (framebuffer_width, framebuffer_height) = window.get_framebuffer_size();
+        if framebuffer_width <= 0 || framebuffer_height <= 0 {
+            return Ok(false);
+        }
+
+        let (width, height) = scaled_size(framebuffer_width as u32, framebuffer_height as u32, scale);
+        if (width, height) == (self.width, self.height) {
+            return Ok(false);
+        }
+
+        self.resize(width, height)?;
+        Ok(true)
+    }
+
+    /// Sets the scale of the windows framebuffer size that [`RenderTexture::track_window`] resizes to, for example
+    /// 0.5 for a half resolution bloom target. `None` stops tracking the window size.
+    pub fn set_window_scale(&mut self, scale: Option<f32>) {
+        self.window_scale = scale;
+    }
+
+    /// Returns the scale of the windows framebuffer size this render texture tracks, if it tracks the window size.
+    pub fn window_scale(&self) -> Option<f32> {
+        self.window_scale
+    }
+
+    /// Returns a rectangle covering the whole render texture, for use with [`crate::blit`].
+    pub fn rect(&self) -> Rect {
+        Rect::from_size(self.width as i32, self.height as i32)
+    }
+
+    /// Internal function returning the framebuffer that is rendered to.
+    pub(crate) fn framebuffer_id(&self) -> GLuint {
+        self.framebuffer_id
+    }
+
+    /// Internal function returning the framebuffer to read from when blitting. Multisampled framebuffers can not be
+    /// scaled by a blit, so they are resolved first in that case.
+    pub(crate) fn read_framebuffer_id(&self, scaled: bool) -> GLuint {
+        if self.is_multisampled() && scaled {
+            self.resolve();
+            self.resolve_framebuffer_id
+        } else {
+            self.framebuffer_id
+        }
+    }
+
+    /// Copies the first color attachment onto the whole window, fitted with the given scaling mode. Parts of the
+    /// window that are not covered are cleared to black. [`ScalingMode::IntegerScaling`] copies with nearest
+    /// filtering to keep pixel art sharp, the other modes with linear filtering. Multisampled windows can not be
+    /// blitted into, so the texture is drawn with [`GlWindow::draw_texture_rect`] instead.
+    pub fn present_to_window(&self, window: &GlWindow, scaling_mode: ScalingMode) -> Result<(), String> {
+        if self.color_formats.is_empty() {
+            return Err("[FerrousGl Error] Can not present a render texture without color attachments".to_string());
+        }
+
+        let (window_width, window_height) = window.get_framebuffer_size();
+        let window_rect = Rect::from_size(window_width, window_height);
+        let dst_rect = scaling_mode.fit(self.width as i32, self.height as i32, window_width, window_height);
+
+        if dst_rect != window_rect {
+            with_framebuffer(0, || unsafe {
+                gl::ClearBufferfv(gl::COLOR, 0, [0.0, 0.0, 0.0, 1.0].as_ptr());
+            });
+        }
+
+        let filter = match scaling_mode {
+            ScalingMode::IntegerScaling => FilterMode::Nearest,
+            ScalingMode::Letterbox | ScalingMode::Stretch => FilterMode::Linear,
+        };
+
+        if default_framebuffer_samples() == 0 {
+            return blit(Some(self), self.rect(), None, dst_rect, filter, BlitMask::COLOR);
+        }
+
+        self.resolve();
+        let sampler = self.present_samplers[(filter == FilterMode::Linear) as usize].get_or_init(|| {
+            Sampler::new(&SamplerSettings {
+                min_filter: filter,
+                mag_filter: filter,
+                ..SamplerSettings::with_wrap(WrapMode::ClampToEdge)
+            })
+        });
+        sampler.bind(0);
+        with_framebuffer(0, || window.draw_texture_rect(self.texture(), self.rect(), dst_rect));
+        sampler.unbind(0);
+
+        Ok(())
+    }
+
+    /// Returns the width of the render texture in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height of the render texture in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Binds the render texture as the current framebuffer and sets the viewport to its size. The previous
+    /// framebuffer and viewport are remembered and restored by [`RenderTexture::unbind`].
     pub fn bind(&self) {
+        let previous_target = RenderTarget::current();
+        // Binding again without unbinding keeps the framebuffer that was bound before the first bind
+        if previous_target.framebuffer_id != self.framebuffer_id {
+            self.previous_target.set(Some(previous_target));
+        }
+
         unsafe {
             gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer_id);
             gl::Viewport(0, 0, self.width as i32, self.height as i32);
         }
     }
 
-    /// Unbinds the render texture (binds the default framebuffer), after this normal rendering can continue.
+    /// Unbinds the render texture, restoring the framebuffer and viewport that were in use before
+    /// [`RenderTexture::bind`]. After this normal rendering can continue, also when render textures were bound
+    /// inside each other.
     pub fn unbind(&self) {
+        match self.previous_target.take() {
+            Some(previous_target) => previous_target.restore(),
+            None => unsafe {
+                gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            },
+        }
+    }
+
+    /// Binds the render texture until the returned guard is dropped, which restores the previous framebuffer and
+    /// viewport and resolves multisampled render textures. See also [`GlWindow::render_to`].
+    pub fn bind_scoped(&self) -> RenderTextureGuard<'_> {
+        let previous_target = RenderTarget::current();
+
         unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer_id);
+            gl::Viewport(0, 0, self.width as i32, self.height as i32);
+        }
+
+        RenderTextureGuard {
+            render_texture: self,
+            previous_target,
+        }
+    }
+
+    /// Redirects rendering into a layer of a texture array instead of the first color texture (or depth texture for
+    /// depth formats). The texture array has to be of the same size as the render texture.
+    pub fn attach_array_layer(&self, array: &TextureArray, layer: u32) -> Result<(), String> {
+        if layer >= array.layers() {
+            return Err(format!(
+                "[FerrousGl Error] Layer {} is outside of the texture array with {} layers",
+                layer,
+                array.layers()
+            ));
+        }
+
+        self.attach_layer(array.id, array.format, array.width(), array.height(), layer)
+    }
+
+    /// Redirects rendering into a slice of a 3D texture instead of the first color texture.
+    /// The 3D texture has to be of the same width and height as the render texture.
+    pub fn attach_volume_slice(&self, volume: &Texture3D, slice: u32) -> Result<(), String> {
+        if slice >= volume.depth() {
+            return Err(format!(
+                "[FerrousGl Error] Slice {} is outside of the 3D texture with {} slices",
+                slice,
+                volume.depth()
+            ));
+        }
+
+        self.attach_layer(volume.id, volume.format, volume.width(), volume.height(), slice)
+    }
+
+    /// Attaches the own color and depth textures again after rendering into a texture array layer or 3D texture slice.
+    pub fn restore_attachments(&self) {
+        if self.is_multisampled() {
+            return;
+        }
+
+        with_framebuffer(self.framebuffer_id, || unsafe {
+            let color_id = self.color_textures.first().map_or(0, |texture| texture.id);
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_2D, color_id, 0);
+
+            // Detaching the combined attachment detaches both depth and stencil
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::DEPTH_STENCIL_ATTACHMENT, gl::TEXTURE_2D, 0, 0);
+            self.attach_depth(false);
+        });
+    }
+
+    /// Internal function to attach the own depth texture or renderbuffer to the bound framebuffer. The framebuffer
+    /// that is rendered to prefers the renderbuffer, the resolve framebuffer can only use the texture.
+    fn attach_depth(&self, resolve: bool) {
+        let Some(format) = self.depth_format() else {
+            return;
+        };
+
+        unsafe {
+            if !resolve && self.depth_renderbuffer_id != 0 {
+                gl::FramebufferRenderbuffer(
+                    gl::FRAMEBUFFER,
+                    depth_attachment_point(format),
+                    gl::RENDERBUFFER,
+                    self.depth_renderbuffer_id,
+                );
+            } else if let Some(ref depth) = self.depth_texture {
+                gl::FramebufferTexture2D(gl::FRAMEBUFFER, depth_attachment_point(format), gl::TEXTURE_2D, depth.id, 0);
+            }
         }
     }
 
-    /// Returns a reference to the color texture that this render texture renders to.
+    /// Internal function to attach a single layer of a layered texture.
+    fn attach_layer(&self, texture_id: GLuint, format: TextureFormat, width: u32, height: u32, layer: u32) -> Result<(), String> {
+        if self.is_multisampled() {
+            return Err("[FerrousGl Error] Can not attach layers to a multisampled render texture".to_string());
+        }
+        if width != self.width || height != self.height {
+            return Err(format!(
+                "[FerrousGl Error] Expected a {}x{} layer to attach, but got {}x{}",
+                self.width, self.height, width, height
+            ));
+        }
+
+        let attachment = if format.is_depth() {
+            depth_attachment_point(format)
+        } else {
+            gl::COLOR_ATTACHMENT0
+        };
+
+        with_framebuffer(self.framebuffer_id, || unsafe {
+            gl::FramebufferTextureLayer(gl::FRAMEBUFFER, attachment, texture_id, 0, layer as i32);
+
+            if gl::CheckFramebufferStatus(gl::FRAMEBUFFER) != gl::FRAMEBUFFER_COMPLETE {
+                return Err("[FerrousGl Error] Framebuffer is not complete!".to_string());
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Clears every attachment of the render texture with its own clear value, see
+    /// [`RenderTextureBuilder::color_attachment_with_clear`]. The render texture does not have to be bound for this.
+    pub fn clear(&self) {
+        with_framebuffer(self.framebuffer_id, || unsafe {
+            for (index, (format, color)) in self.color_formats.iter().zip(&self.clear_colors).enumerate() {
+                if format.is_integer() {
+                    let color = color.to_array().map(|value| value as u32);
+                    gl::ClearBufferuiv(gl::COLOR, index as i32, color.as_ptr());
+                } else {
+                    gl::ClearBufferfv(gl::COLOR, index as i32, color.to_array().as_ptr());
+                }
+            }
+
+            match self.depth_format() {
+                Some(format) if format.has_stencil() => {
+                    gl::ClearBufferfi(gl::DEPTH_STENCIL, 0, self.clear_depth, self.clear_stencil);
+                }
+                Some(_) => gl::ClearBufferfv(gl::DEPTH, 0, &self.clear_depth),
+                None => {}
+            }
+        });
+    }
+
+    /// Sets the value a color attachment is cleared with by [`RenderTexture::clear`].
+    /// Integer attachments are cleared with the color channels converted to integers.
+    pub fn set_clear_color(&mut self, index: usize, color: Vec4) -> Result<(), String> {
+        let clear_color = self.clear_colors.get_mut(index).ok_or_else(|| {
+            format!("[FerrousGl Error] The render texture has no color attachment {}", index)
+        })?;
+
+        *clear_color = color;
+        Ok(())
+    }
+
+    /// Returns a reference to the first color texture that this render texture renders to. The textures of
+    /// multisampled render textures hold the result of the last [`RenderTexture::resolve`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the render texture was built without color attachments, such as a depth-only render texture.
+    /// Use [`RenderTexture::color_texture`] with index 0 to handle that case.
     pub fn texture(&self) -> &Texture {
-        &self.texture
+        &self.color_textures[0]
     }
 
-    /// Returns a reference to the depth texture if it exists.
+    /// Returns a reference to the color texture of an attachment, in the order they were added to the builder.
+    pub fn color_texture(&self, index: usize) -> Option<&Texture> {
+        self.color_textures.get(index)
+    }
+
+    /// Returns the amount of color textures this render texture renders to.
+    pub fn color_texture_count(&self) -> usize {
+        self.color_textures.len()
+    }
+
+    /// Returns a reference to the depth texture if it exists. Render textures with a depth renderbuffer have no
+    /// depth texture.
     pub fn depth_texture(&self) -> Option<&Texture> {
         self.depth_texture.as_ref()
     }
+
+    /// Returns the format of the depth attachment if it exists.
+    pub fn depth_format(&self) -> Option<TextureFormat> {
+        match self.depth_attachment {
+            DepthAttachment::None => None,
+            DepthAttachment::Texture(format) | DepthAttachment::Renderbuffer(format) => Some(format),
+        }
+    }
 }
 
 impl Drop for RenderTexture {
     /// Cleans up the framebuffer when it goes out of scope.
     fn drop(&mut self) {
+        self.delete_renderbuffers();
+
         unsafe {
             gl::DeleteFramebuffers(1, &self.framebuffer_id);
+            if self.resolve_framebuffer_id != 0 {
+                gl::DeleteFramebuffers(1, &self.resolve_framebuffer_id);
+            }
+        }
+    }
+}
+
+/// Guard returned by [`RenderTexture::bind_scoped`], which keeps the render texture bound while it is alive.
+pub struct RenderTextureGuard<'a> {
+    render_texture: &'a RenderTexture,
+    previous_target: RenderTarget,
+}
+
+impl RenderTextureGuard<'_> {
+    /// Returns the render texture that is bound.
+    pub fn render_texture(&self) -> &RenderTexture {
+        self.render_texture
+    }
+}
+
+impl Drop for RenderTextureGuard<'_> {
+    /// Resolves the render texture if it is multisampled and restores the previous framebuffer and viewport.
+    fn drop(&mut self) {
+        self.render_texture.resolve();
+        self.previous_target.restore();
+    }
+}
+
+/// Internal struct holding the framebuffer and viewport that were in use before binding a render texture.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RenderTarget {
+    framebuffer_id: GLuint,
+    viewport: [i32; 4],
+}
+
+impl RenderTarget {
+    /// Returns the current bound framebuffer and viewport.
+    pub(crate) fn current() -> Self {
+        let mut framebuffer_id = 0;
+        let mut viewport = [0; 4];
+
+        unsafe {
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut framebuffer_id);
+            gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+        }
+
+        RenderTarget {
+            framebuffer_id: framebuffer_id as GLuint,
+            viewport,
+        }
+    }
+
+    /// Binds the framebuffer and sets the viewport again.
+    pub(crate) fn restore(self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer_id);
+            gl::Viewport(self.viewport[0], self.viewport[1], self.viewport[2], self.viewport[3]);
+        }
+    }
+}
+
+/// Internal function to run OpenGL calls with a framebuffer bound, restoring the previous binding afterwards.
+fn with_framebuffer<R>(framebuffer_id: GLuint, f: impl FnOnce() -> R) -> R {
+    unsafe {
+        let mut previous_framebuffer = 0;
+        gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut previous_framebuffer);
+        gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer_id);
+
+        let result = f();
+
+        gl::BindFramebuffer(gl::FRAMEBUFFER, previous_framebuffer as GLuint);
+        result
+    }
+}
+
+/// Internal function scaling a size, keeping it at least one pixel large.
+fn scaled_size(width: u32, height: u32, scale: f32) -> (u32, u32) {
+    (
+        ((width as f32 * scale).round() as u32).max(1),
+        ((height as f32 * scale).round() as u32).max(1),
+    )
+}
+
+/// Internal function returning the framebuffer attachment point of a depth format.
+fn depth_attachment_point(format: TextureFormat) -> GLenum {
+    if format.has_stencil() {
+        gl::DEPTH_STENCIL_ATTACHMENT
+    } else {
+        gl::DEPTH_ATTACHMENT
+    }
+}
+
+/// Enum representing the depth (and stencil) attachment of a render texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepthAttachment {
+    /// No depth attachment, depth testing has no effect while rendering to the render texture.
+    None,
+    /// A depth texture of the given depth format, which can be sampled after rendering, for example as a shadow map.
+    Texture(TextureFormat),
+    /// A renderbuffer of the given depth format. Renderbuffers can not be sampled, but can be faster to render to
+    /// when the depth and stencil values are only needed while rendering.
+    Renderbuffer(TextureFormat),
+}
+
+/// Builder for render textures with any amount of color attachments, each with its own format and clear value.
+/// Fragment shaders write to the attachments in the order they were added, using `layout(location = n) out`.
+pub struct RenderTextureBuilder {
+    width: u32,
+    height: u32,
+    color_attachments: Vec<(TextureFormat, Vec4)>,
+    depth_attachment: DepthAttachment,
+    clear_depth: f32,
+    clear_stencil: i32,
+    samples: u32,
+    window_scale: Option<f32>,
+}
+
+impl RenderTextureBuilder {
+    /// Creates a new builder for a render texture with the specified width and height and no attachments.
+    pub fn new(width: u32, height: u32) -> Self {
+        RenderTextureBuilder {
+            width,
+            height,
+            color_attachments: Vec::new(),
+            depth_attachment: DepthAttachment::None,
+            clear_depth: 1.0,
+            clear_stencil: 0,
+            samples: 0,
+            window_scale: None,
         }
     }
+
+    /// Adds a color attachment of the given format, which is cleared to transparent black.
+    pub fn color_attachment(self, format: TextureFormat) -> Self {
+        self.color_attachment_with_clear(format, Vec4::ZERO)
+    }
+
+    /// Adds a color attachment of the given format, which is cleared to the given color by [`RenderTexture::clear`].
+    pub fn color_attachment_with_clear(mut self, format: TextureFormat, clear_color: Vec4) -> Self {
+        self.color_attachments.push((format, clear_color));
+        self
+    }
+
+    /// Sets whether a [`TextureFormat::Depth24`] depth texture is attached.
+    pub fn with_depth(self, with_depth: bool) -> Self {
+        self.depth_attachment(if with_depth {
+            DepthAttachment::Texture(TextureFormat::Depth24)
+        } else {
+            DepthAttachment::None
+        })
+    }
+
+    /// Sets the depth attachment. Use a format with stencil, such as [`TextureFormat::Depth24Stencil8`],
+    /// to use the stencil test while rendering to the render texture.
+    pub fn depth_attachment(mut self, depth_attachment: DepthAttachment) -> Self {
+        self.depth_attachment = depth_attachment;
+        self
+    }
+
+    /// Sets the value the depth texture is cleared to by [`RenderTexture::clear`], 1.0 by default.
+    pub fn depth_clear_value(mut self, depth: f32) -> Self {
+        self.clear_depth = depth;
+        self
+    }
+
+    /// Sets the value the stencil buffer is cleared to by [`RenderTexture::clear`], 0 by default.
+    pub fn stencil_clear_value(mut self, stencil: i32) -> Self {
+        self.clear_stencil = stencil;
+        self
+    }
+
+    /// Sets the amount of samples per pixel for multisample anti-aliasing, for example 4. 0 or 1 disables
+    /// multisampling. Multisampled render textures have to be resolved with [`RenderTexture::resolve`] before
+    /// their textures can be used.
+    pub fn samples(mut self, samples: u32) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    /// Makes the render texture track the windows framebuffer size multiplied by `scale`, see
+    /// [`RenderTexture::track_window`]. The size passed to the builder is used until the first call of it, so usually
+    /// the result of [`GlWindow::get_framebuffer_size`] is passed to the builder.
+    pub fn track_window_size(mut self, scale: f32) -> Self {
+        self.window_scale = Some(scale);
+        self
+    }
+
+    /// Creates the render texture with all of its attachments.
+    pub fn build(self) -> Result<RenderTexture, String> {
+        if self.color_attachments.is_empty() && self.depth_attachment == DepthAttachment::None {
+            return Err("[FerrousGl Error] A render texture needs at least one attachment".to_string());
+        }
+
+        let max_attachments = unsafe {
+            let (mut max_color_attachments, mut max_draw_buffers) = (0, 0);
+            gl::GetIntegerv(gl::MAX_COLOR_ATTACHMENTS, &mut max_color_attachments);
+            gl::GetIntegerv(gl::MAX_DRAW_BUFFERS, &mut max_draw_buffers);
+            max_color_attachments.min(max_draw_buffers) as usize
+        };
+        if self.color_attachments.len() > max_attachments {
+            return Err(format!(
+                "[FerrousGl Error] The graphics driver supports at most {} color attachments, but {} were requested",
+                max_attachments,
+                self.color_attachments.len()
+            ));
+        }
+
+        if let Some(&(format, _)) = self
+            .color_attachments
+            .iter()
+            .find(|(format, _)| format.is_depth() || format.is_compressed())
+        {
+            return Err(format!("[FerrousGl Error] Can not render colors into a {:?} texture", format));
+        }
+        if let DepthAttachment::Texture(format) | DepthAttachment::Renderbuffer(format) = self.depth_attachment {
+            if !format.is_depth() {
+                return Err(format!("[FerrousGl Error] Can not use a {:?} texture as depth attachment", format));
+            }
+        }
+
+        let max_samples = unsafe {
+            let mut max_samples = 0;
+            gl::GetIntegerv(gl::MAX_SAMPLES, &mut max_samples);
+            max_samples as u32
+        };
+        if self.samples > max_samples {
+            return Err(format!(
+                "[FerrousGl Error] The graphics driver supports at most {} samples, but {} were requested",
+                max_samples, self.samples
+            ));
+        }
+
+        let (mut framebuffer_id, mut resolve_framebuffer_id) = (0, 0);
+        unsafe {
+            gl::GenFramebuffers(1, &mut framebuffer_id);
+            if self.samples > 1 {
+                gl::GenFramebuffers(1, &mut resolve_framebuffer_id);
+            }
+        }
+
+        // From here on the framebuffers and renderbuffers are deleted by dropping the render texture
+        let mut render_texture = RenderTexture {
+            framebuffer_id,
+            resolve_framebuffer_id,
+            color_formats: self.color_attachments.iter().map(|&(format, _)| format).collect(),
+            color_textures: Vec::new(),
+            color_renderbuffer_ids: Vec::new(),
+            clear_colors: self.color_attachments.iter().map(|&(_, clear_color)| clear_color).collect(),
+            depth_attachment: self.depth_attachment,
+            depth_texture: None,
+            depth_renderbuffer_id: 0,
+            clear_depth: self.clear_depth,
+            clear_stencil: self.clear_stencil,
+            samples: self.samples,
+            window_scale: self.window_scale,
+            previous_target: Cell::new(None),
+            present_samplers: [OnceCell::new(), OnceCell::new()],
+            width: self.width,
+            height: self.height,
+        };
+        render_texture.allocate()?;
+
+        Ok(render_texture)
+    }
 }
@@ -0,0 +1,279 @@
+extern crate gl;
+
+use gl::types::*;
+
+use crate::capabilities::max_anisotropy;
+use crate::texture::{FilterMode, MipmapType};
+
+/// Parameter of `EXT_texture_filter_anisotropic`, which is not part of the core OpenGL bindings.
+const TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FE;
+
+/// Represents an OpenGL sampler object. A sampler holds sampler settings independently of any texture and overrides
+/// the settings of whatever texture is bound to the same texture unit. This allows sampling the same texture in
+/// different ways, for example a shadow map with and without depth comparison, or pixel art with nearest and linear filtering.
+pub struct Sampler {
+    id: GLuint,
+    settings: SamplerSettings,
+}
+
+impl Sampler {
+    /// Creates a new sampler with the given settings.
+    pub fn new(settings: &SamplerSettings) -> Self {
+        let mut sampler_id = 0;
+
+        unsafe {
+            gl::GenSamplers(1, &mut sampler_id);
+        }
+
+        settings.apply(ParameterTarget::Sampler(sampler_id));
+
+        Sampler {
+            id: sampler_id,
+            settings: *settings,
+        }
+    }
+
+    /// Changes the settings of the sampler.
+    pub fn set_settings(&mut self, settings: &SamplerSettings) {
+        settings.apply(ParameterTarget::Sampler(self.id));
+        self.settings = *settings;
+    }
+
+    /// Returns the settings of the sampler.
+    pub fn settings(&self) -> SamplerSettings {
+        self.settings
+    }
+
+    /// Binds the sampler to a specific texture unit. The texture bound to the same unit is then sampled using
+    /// this samplers settings instead of its own, until the sampler is unbound.
+    pub fn bind(&self, texture_unit: u32) {
+        unsafe {
+            gl::BindSampler(texture_unit, self.id);
+        }
+    }
+
+    /// Unbinds the sampler from a specific texture unit, textures bound to it use their own settings again.
+    pub fn unbind(&self, texture_unit: u32) {
+        unsafe {
+            gl::BindSampler(texture_unit, 0);
+        }
+    }
+}
+
+impl Drop for Sampler {
+    /// Cleans up the sampler when it goes out of scope.
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteSamplers(1, &self.id);
+        }
+    }
+}
+
+/// Struct describing how a texture is sampled in shaders: wrapping, filtering, level of detail and depth comparison.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SamplerSettings {
+    pub wrap_s: WrapMode,
+    pub wrap_t: WrapMode,
+    pub wrap_r: WrapMode,
+    /// The color returned outside of the texture when using [`WrapMode::ClampToBorder`].
+    pub border_color: [f32; 4],
+    /// The filter used within a mipmap level when the texture is minified, [`FilterMode::Nearest`] keeps pixel art sharp.
+    pub min_filter: FilterMode,
+    /// The filter used when the texture is magnified.
+    pub mag_filter: FilterMode,
+    /// How mipmaps are used when the texture is minified: [`MipmapType::Nearest`] picks the closest level and
+    /// [`MipmapType::Linear`] blends between the two closest levels. The texture needs mipmaps for anything but
+    /// [`MipmapType::None`].
+    pub mipmap: MipmapType,
+    /// The maximum anisotropy level, `1.0` disables anisotropic filtering. The value is clamped to what the driver supports
+    /// and ignored if `EXT_texture_filter_anisotropic` is not available.
+    pub anisotropy: f32,
+    pub lod_bias: f32,
+    pub min_lod: f32,
+    pub max_lod: f32,
+    /// Enables depth comparison for depth textures, which are then sampled with `sampler2DShadow` in shaders.
+    pub compare: Option<CompareFunction>,
+}
+
+impl SamplerSettings {
+    /// Creates sampler settings which use the same wrap mode on all axes.
+    pub fn with_wrap(wrap: WrapMode) -> Self {
+        Self {
+            wrap_s: wrap,
+            wrap_t: wrap,
+            wrap_r: wrap,
+            ..Default::default()
+        }
+    }
+
+    /// Internal function to apply the settings to a texture or sampler object.
+    pub(crate) fn apply(&self, target: ParameterTarget) {
+        unsafe {
+            target.set_i(gl::TEXTURE_WRAP_S, GLenum::from(self.wrap_s) as i32);
+            target.set_i(gl::TEXTURE_WRAP_T, GLenum::from(self.wrap_t) as i32);
+            target.set_i(gl::TEXTURE_WRAP_R, GLenum::from(self.wrap_r) as i32);
+            target.set_fv(gl::TEXTURE_BORDER_COLOR, &self.border_color);
+
+            target.set_i(gl::TEXTURE_MIN_FILTER, min_filter(self.mipmap, self.min_filter) as i32);
+            target.set_i(gl::TEXTURE_MAG_FILTER, mag_filter(self.mag_filter) as i32);
+
+            target.set_f(gl::TEXTURE_LOD_BIAS, self.lod_bias);
+            target.set_f(gl::TEXTURE_MIN_LOD, self.min_lod);
+            target.set_f(gl::TEXTURE_MAX_LOD, self.max_lod);
+
+            if let Some(max_anisotropy) = max_anisotropy() {
+                target.set_f(TEXTURE_MAX_ANISOTROPY, self.anisotropy.clamp(1.0, max_anisotropy));
+            }
+
+            match self.compare {
+                Some(function) => {
+                    target.set_i(gl::TEXTURE_COMPARE_MODE, gl::COMPARE_REF_TO_TEXTURE as i32);
+                    target.set_i(gl::TEXTURE_COMPARE_FUNC, GLenum::from(function) as i32);
+                }
+                None => {
+                    target.set_i(gl::TEXTURE_COMPARE_MODE, gl::NONE as i32);
+                }
+            }
+        }
+    }
+}
+
+impl Default for SamplerSettings {
+    fn default() -> Self {
+        Self {
+            wrap_s: WrapMode::Repeat,
+            wrap_t: WrapMode::Repeat,
+            wrap_r: WrapMode::Repeat,
+            border_color: [0.0, 0.0, 0.0, 0.0],
+            min_filter: FilterMode::Linear,
+            mag_filter: FilterMode::Linear,
+            mipmap: MipmapType::None,
+            anisotropy: 1.0,
+            lod_bias: 0.0,
+            min_lod: -1000.0,
+            max_lod: 1000.0,
+            compare: None,
+        }
+    }
+}
+
+/// Internal enum for the object sampler settings are applied to.
+pub(crate) enum ParameterTarget {
+    /// A texture bound to the given target, such as `GL_TEXTURE_2D`.
+    Texture(GLenum),
+    /// A sampler object.
+    Sampler(GLuint),
+}
+
+impl ParameterTarget {
+    unsafe fn set_i(&self, parameter: GLenum, value: i32) {
+        match *self {
+            ParameterTarget::Texture(target) => gl::TexParameteri(target, parameter, value),
+            ParameterTarget::Sampler(sampler) => gl::SamplerParameteri(sampler, parameter, value),
+        }
+    }
+
+    unsafe fn set_f(&self, parameter: GLenum, value: f32) {
+        match *self {
+            ParameterTarget::Texture(target) => gl::TexParameterf(target, parameter, value),
+            ParameterTarget::Sampler(sampler) => gl::SamplerParameterf(sampler, parameter, value),
+        }
+    }
+
+    unsafe fn set_fv(&self, parameter: GLenum, value: &[f32; 4]) {
+        match *self {
+            ParameterTarget::Texture(target) => gl::TexParameterfv(target, parameter, value.as_ptr()),
+            ParameterTarget::Sampler(sampler) => gl::SamplerParameterfv(sampler, parameter, value.as_ptr()),
+        }
+    }
+}
+
+/// Internal function returning the minification filter for a mipmap type and base filter.
+/// The base filter is used within a mipmap level, the mipmap type between mipmap levels.
+pub(crate) fn min_filter(mipmap: MipmapType, filter: FilterMode) -> GLenum {
+    match (mipmap, filter) {
+        (MipmapType::None, FilterMode::Linear) => gl::LINEAR,
+        (MipmapType::None, FilterMode::Nearest) => gl::NEAREST,
+        (MipmapType::Nearest, FilterMode::Linear) => gl::LINEAR_MIPMAP_NEAREST,
+        (MipmapType::Nearest, FilterMode::Nearest) => gl::NEAREST_MIPMAP_NEAREST,
+        (MipmapType::Linear, FilterMode::Linear) => gl::LINEAR_MIPMAP_LINEAR,
+        (MipmapType::Linear, FilterMode::Nearest) => gl::NEAREST_MIPMAP_LINEAR,
+    }
+}
+
+/// Internal function returning the magnification filter for a filter mode.
+pub(crate) fn mag_filter(filter: FilterMode) -> GLenum {
+    match filter {
+        FilterMode::Linear => gl::LINEAR,
+        FilterMode::Nearest => gl::NEAREST,
+    }
+}
+
+/// Enum representing what happens when sampling a texture outside of the `[0, 1]` range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapMode {
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+    ClampToBorder,
+}
+
+impl From<WrapMode> for GLenum {
+    fn from(mode: WrapMode) -> Self {
+        match mode {
+            WrapMode::Repeat => gl::REPEAT,
+            WrapMode::MirroredRepeat => gl::MIRRORED_REPEAT,
+            WrapMode::ClampToEdge => gl::CLAMP_TO_EDGE,
+            WrapMode::ClampToBorder => gl::CLAMP_TO_BORDER,
+        }
+    }
+}
+
+/// Enum representing comparison functions, used for depth comparison when sampling shadow maps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareFunction {
+    Never,
+    Less,
+    Equal,
+    LessOrEqual,
+    Greater,
+    NotEqual,
+    GreaterOrEqual,
+    Always,
+}
+
+impl From<CompareFunction> for GLenum {
+    fn from(function: CompareFunction) -> Self {
+        match function {
+            CompareFunction::Never => gl::NEVER,
+            CompareFunction::Less => gl::LESS,
+            CompareFunction::Equal => gl::EQUAL,
+            CompareFunction::LessOrEqual => gl::LEQUAL,
+            CompareFunction::Greater => gl::GREATER,
+            CompareFunction::NotEqual => gl::NOTEQUAL,
+            CompareFunction::GreaterOrEqual => gl::GEQUAL,
+            CompareFunction::Always => gl::ALWAYS,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn min_filter_uses_filter_within_and_mipmap_between_levels() {
+        let table = [
+            (MipmapType::None, FilterMode::Nearest, gl::NEAREST),
+            (MipmapType::None, FilterMode::Linear, gl::LINEAR),
+            (MipmapType::Nearest, FilterMode::Nearest, gl::NEAREST_MIPMAP_NEAREST),
+            (MipmapType::Nearest, FilterMode::Linear, gl::LINEAR_MIPMAP_NEAREST),
+            (MipmapType::Linear, FilterMode::Nearest, gl::NEAREST_MIPMAP_LINEAR),
+            (MipmapType::Linear, FilterMode::Linear, gl::LINEAR_MIPMAP_LINEAR),
+        ];
+
+        for (mipmap, filter, expected) in table {
+            assert_eq!(min_filter(mipmap, filter), expected, "{:?} mipmaps with {:?} filtering", mipmap, filter);
+        }
+    }
+}
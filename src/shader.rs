@@ -8,6 +8,8 @@ use std::fs::File;
 use std::io::Read;
 use std::path::Path;
 
+use crate::fullscreen::FULLSCREEN_VERTEX_SHADER;
+
 /// Represents a shader to be used for telling the GPU how to "fill in" a meshes vertices.
 pub struct Shader {
     pub id: GLuint,
@@ -29,6 +31,12 @@ impl Shader {
         Shader { id: shader_program }
     }
 
+    /// Creates a new shader for [`crate::GlWindow::draw_fullscreen`] from a fragment shader string. The built-in vertex
+    /// shader passes the texture coordinate across the viewport as `in vec2 TexCoord` in the range `[0, 1]`.
+    pub fn new_fullscreen_from_source(fragment_source: &str) -> Self {
+        Shader::new_from_source(FULLSCREEN_VERTEX_SHADER, fragment_source)
+    }
+
     /// Creates a new shader using a vertex shader file path and fragment file path.
     /// After this it is ready to be used for rendering.
     pub fn new_from_file(vertex_path: &Path, fragment_path: &Path) -> Result<Self, String> {
@@ -47,6 +55,33 @@ impl Shader {
         Ok(Self::new_from_source(&vertex_source, &fragment_source))
     }
 
+    /// Creates a new transform feedback shader from a vertex shader string. The outputs of the vertex shader named in
+    /// `varyings` are captured into the vertex buffers passed to [`crate::GlWindow::capture_transform_feedback`].
+    /// With [`TransformFeedbackMode::Interleaved`] all varyings are written into one buffer after each other,
+    /// with [`TransformFeedbackMode::Separate`] every varying is written into its own buffer.
+    pub fn new_transform_feedback_from_source(vertex_source: &str, varyings: &[&str], mode: TransformFeedbackMode) -> Result<Self, String> {
+        let vertex_shader = Shader::compile_shader(gl::VERTEX_SHADER, vertex_source)?;
+        let shader_program = Shader::link_program_with_varyings(&[vertex_shader], varyings, mode);
+
+        unsafe {
+            gl::DeleteShader(vertex_shader);
+        }
+
+        shader_program.map(|id| Shader { id })
+    }
+
+    /// Creates a new transform feedback shader from a vertex shader file path.
+    /// See [`Shader::new_transform_feedback_from_source`] for more information.
+    pub fn new_transform_feedback_from_file(vertex_path: &Path, varyings: &[&str], mode: TransformFeedbackMode) -> Result<Self, String> {
+        let mut vertex_file = File::open(vertex_path)
+            .map_err(|e| format!("[FerrousGl Error] Failed to open vertex shader file: {}", e))?;
+        let mut vertex_source = String::new();
+        vertex_file.read_to_string(&mut vertex_source)
+            .map_err(|e| format!("[FerrousGl Error] Failed to read vertex shader file: {}", e))?;
+
+        Self::new_transform_feedback_from_source(&vertex_source, varyings, mode)
+    }
+
     /// Recompiles the shader from the given vertex and fragment shader files.
     /// Returns Ok(()) on success, or an error message if compilation fails.
     pub fn recompile_from_file(&mut self, vertex_path: &Path, fragment_path: &Path) -> Result<(), String> {
@@ -98,10 +133,36 @@ impl Shader {
 
     /// Internal function to link the shader program.
     fn link_program(vertex_shader: GLuint, fragment_shader: GLuint) -> Result<GLuint, String> {
+        Shader::link_program_with_varyings(&[vertex_shader, fragment_shader], &[], TransformFeedbackMode::Interleaved)
+    }
+
+    /// Internal function to link the shader program, capturing the given varyings with transform feedback.
+    fn link_program_with_varyings(shaders: &[GLuint], varyings: &[&str], mode: TransformFeedbackMode) -> Result<GLuint, String> {
+        let varyings = varyings
+            .iter()
+            .map(|varying| {
+                CString::new(*varying)
+                    .map_err(|_| format!("[FerrousGl Error] Invalid varying name {:?}, it contains a nul byte", varying))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
         let program = unsafe { gl::CreateProgram() };
         unsafe {
-            gl::AttachShader(program, vertex_shader);
-            gl::AttachShader(program, fragment_shader);
+            for &shader in shaders {
+                gl::AttachShader(program, shader);
+            }
+
+            if !varyings.is_empty() {
+                let varying_pointers = varyings.iter().map(|varying| varying.as_ptr()).collect::<Vec<_>>();
+
+                gl::TransformFeedbackVaryings(
+                    program,
+                    varying_pointers.len() as GLsizei,
+                    varying_pointers.as_ptr(),
+                    mode.into(),
+                );
+            }
+
             gl::LinkProgram(program);
         }
 
@@ -223,6 +284,22 @@ impl Shader {
     }
 }
 
+/// Enum representing how transform feedback varyings are written into buffers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransformFeedbackMode {
+    Interleaved,
+    Separate,
+}
+
+impl From<TransformFeedbackMode> for GLenum {
+    fn from(mode: TransformFeedbackMode) -> Self {
+        match mode {
+            TransformFeedbackMode::Interleaved => gl::INTERLEAVED_ATTRIBS,
+            TransformFeedbackMode::Separate => gl::SEPARATE_ATTRIBS,
+        }
+    }
+}
+
 fn create_whitespace_cstring_with_len(len: usize) -> CString {
     let mut buffer: Vec<u8> = Vec::with_capacity(len + 1);
     buffer.extend([b' '].iter().cycle().take(len));
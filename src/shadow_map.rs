@@ -0,0 +1,552 @@
+extern crate gl;
+
+use glam::{Mat4, Vec3};
+
+use crate::{
+    BoundingBox, CompareFunction, DepthAttachment, FilterMode, RenderTexture, Sampler, SamplerSettings, Shader, Texture,
+    TextureArray, TextureFormat, WrapMode,
+};
+
+/// The largest amount of cascades supported by [`SHADOW_CASCADE_GLSL`].
+pub const MAX_CASCADES: u32 = 8;
+
+/// Enum representing the light a shadow map is rendered from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShadowLight {
+    /// A light infinitely far away shining in `direction`, like the sun. Uses an orthographic projection.
+    Directional { direction: Vec3 },
+    /// A light at `position` shining in `direction` in a cone with the full opening angle `cone_angle` in radians,
+    /// reaching up to `range`. Uses a perspective projection.
+    Spot { position: Vec3, direction: Vec3, cone_angle: f32, range: f32 },
+}
+
+/// A depth texture rendered from the view of a directional or spot light, used to find out which parts of a scene
+/// are in shadow. Render the shadow casters with [`ShadowMap::render`] and a shader transforming vertices by
+/// [`ShadowMap::light_space_matrix`], then sample it with [`SHADOW_PCF_GLSL`] or [`SHADOW_PCSS_GLSL`].
+pub struct ShadowMap {
+    render_texture: RenderTexture,
+    compare_sampler: Sampler,
+    light: ShadowLight,
+    view: Mat4,
+    projection: Mat4,
+}
+
+impl ShadowMap {
+    /// Creates a new square shadow map with the given resolution and a [`TextureFormat::Depth24`] depth texture.
+    pub fn new(resolution: u32) -> Result<Self, String> {
+        Self::new_with_format(resolution, TextureFormat::Depth24)
+    }
+
+    /// Creates a new square shadow map with the given resolution and depth format.
+    pub fn new_with_format(resolution: u32, format: TextureFormat) -> Result<Self, String> {
+        let render_texture = RenderTexture::builder(resolution, resolution)
+            .depth_attachment(DepthAttachment::Texture(format))
+            .build()?;
+
+        Ok(ShadowMap {
+            render_texture,
+            compare_sampler: Sampler::new(&compare_sampler_settings()),
+            light: ShadowLight::Directional { direction: Vec3::NEG_Y },
+            view: Mat4::IDENTITY,
+            projection: Mat4::IDENTITY,
+        })
+    }
+
+    /// Sets the light and fits its frustum tightly around the scene bounds, so the whole scene casts and receives
+    /// shadows with as much detail as possible. Call this again whenever the light or the scene bounds change.
+    pub fn fit(&mut self, light: ShadowLight, scene_bounds: &BoundingBox) {
+        self.light = light;
+        (self.view, self.projection) = match light {
+            ShadowLight::Directional { direction } => directional_matrices(direction, scene_bounds),
+            ShadowLight::Spot {
+                position,
+                direction,
+                cone_angle,
+                range,
+            } => spot_matrices(position, direction, cone_angle, range, scene_bounds),
+        };
+    }
+
+    /// Binds the shadow map, clears its depth and runs the closure, in which the shadow casters are rendered.
+    /// Afterwards the previous framebuffer and viewport are restored. Depth testing has to be enabled.
+    pub fn render<R>(&self, f: impl FnOnce() -> R) -> R {
+        let _guard = self.render_texture.bind_scoped();
+        self.render_texture.clear();
+        f()
+    }
+
+    /// Binds the depth texture with a depth comparison sampler to a texture unit, for sampling it as
+    /// `sampler2DShadow` with hardware filtered comparisons.
+    pub fn bind(&self, texture_unit: u32) {
+        self.depth_texture().bind(texture_unit);
+        self.compare_sampler.bind(texture_unit);
+    }
+
+    /// Binds the depth texture without depth comparison to a texture unit, for reading the raw depth as `sampler2D`,
+    /// which the blocker search of [`SHADOW_PCSS_GLSL`] needs.
+    pub fn bind_depth(&self, texture_unit: u32) {
+        self.depth_texture().bind(texture_unit);
+    }
+
+    /// Unbinds the depth texture and the comparison sampler from a texture unit.
+    pub fn unbind(&self, texture_unit: u32) {
+        self.compare_sampler.unbind(texture_unit);
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + texture_unit);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+    }
+
+    /// Binds the shadow map to a texture unit and sets the `shadowMap` and `lightSpaceMatrix` uniforms of the bound
+    /// shader.
+    pub fn apply_uniforms(&self, shader: &Shader, texture_unit: u32) {
+        self.bind(texture_unit);
+        shader.set_uniform_texture("shadowMap", texture_unit);
+        shader.set_uniform_matrix_4fv("lightSpaceMatrix", self.light_space_matrix().to_cols_array().as_ref());
+    }
+
+    /// Returns the light the shadow map was last fitted for.
+    pub fn light(&self) -> ShadowLight {
+        self.light
+    }
+
+    /// Returns the view matrix of the light.
+    pub fn light_view(&self) -> Mat4 {
+        self.view
+    }
+
+    /// Returns the projection matrix of the light.
+    pub fn light_projection(&self) -> Mat4 {
+        self.projection
+    }
+
+    /// Returns the matrix transforming world positions into the clip space of the light, `projection * view`.
+    pub fn light_space_matrix(&self) -> Mat4 {
+        self.projection * self.view
+    }
+
+    /// Returns the depth texture of the shadow map.
+    pub fn depth_texture(&self) -> &Texture {
+        self.render_texture
+            .depth_texture()
+            .expect("[FerrousGl Error] Shadow maps always have a depth texture")
+    }
+
+    /// Returns the render texture the shadow map is rendered into.
+    pub fn render_texture(&self) -> &RenderTexture {
+        &self.render_texture
+    }
+}
+
+/// Struct describing the perspective camera the cascades of a [`CascadedShadowMap`] are fitted to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CascadeCamera {
+    pub view: Mat4,
+    /// The vertical field of view in radians.
+    pub fov_y: f32,
+    pub aspect: f32,
+    pub near: f32,
+    pub far: f32,
+}
+
+/// Shadow maps for large scenes from a directional light, split into cascades along the view distance of the camera.
+/// Every cascade covers a part of the camera frustum with its own layer of a depth texture array, so shadows close to
+/// the camera are detailed while distant shadows still exist. Sample it with [`SHADOW_CASCADE_GLSL`].
+pub struct CascadedShadowMap {
+    array: TextureArray,
+    render_texture: RenderTexture,
+    compare_sampler: Sampler,
+    split_lambda: f32,
+    splits: Vec<f32>,
+    matrices: Vec<Mat4>,
+}
+
+impl CascadedShadowMap {
+    /// Creates new cascaded shadow maps with the given amount of cascades (up to [`MAX_CASCADES`]), each with a square
+    /// [`TextureFormat::Depth24`] layer of the given resolution.
+    pub fn new(resolution: u32, cascade_count: u32) -> Result<Self, String> {
+        if cascade_count == 0 || cascade_count > MAX_CASCADES {
+            return Err(format!(
+                "[FerrousGl Error] Cascaded shadow maps support 1 to {} cascades, but {} were requested",
+                MAX_CASCADES, cascade_count
+            ));
+        }
+
+        let format = TextureFormat::Depth24;
+        let array = TextureArray::new_empty(resolution, resolution, cascade_count, format)?;
+        // The own depth attachment is replaced by a layer of the texture array before rendering each cascade
+        let render_texture = RenderTexture::builder(resolution, resolution)
+            .depth_attachment(DepthAttachment::Renderbuffer(format))
+            .build()?;
+
+        Ok(CascadedShadowMap {
+            array,
+            render_texture,
+            compare_sampler: Sampler::new(&compare_sampler_settings()),
+            split_lambda: 0.75,
+            splits: vec![0.0; cascade_count as usize],
+            matrices: vec![Mat4::IDENTITY; cascade_count as usize],
+        })
+    }
+
+    /// Sets how the camera frustum is split between cascades, from 0.0 for evenly spaced splits to 1.0 for
+    /// logarithmic splits, which give close cascades more detail. Defaults to 0.75.
+    pub fn set_split_lambda(&mut self, split_lambda: f32) {
+        self.split_lambda = split_lambda.clamp(0.0, 1.0);
+    }
+
+    /// Fits the cascades to the camera for a directional light shining in `direction`. The depth range of every
+    /// cascade is extended to the scene bounds, so shadow casters outside of the camera frustum still cast shadows.
+    /// Call this every frame after the camera moved.
+    pub fn fit(&mut self, direction: Vec3, camera: &CascadeCamera, scene_bounds: &BoundingBox) {
+        let direction = direction.normalize_or(Vec3::NEG_Y);
+        let up = up_vector(direction);
+        let rotation = Mat4::look_at_rh(Vec3::ZERO, direction, up);
+        let resolution = self.array.width() as f32;
+        let cascade_count = self.splits.len();
+
+        let mut split_near = camera.near;
+        for cascade in 0..cascade_count {
+            let split_far = split_distance(camera.near, camera.far, self.split_lambda, cascade + 1, cascade_count);
+
+            // Corners of the camera frustum slice in world space
+            let projection = Mat4::perspective_rh_gl(camera.fov_y, camera.aspect, split_near, split_far);
+            let inverse = (projection * camera.view).inverse();
+            let corners = ndc_corners().map(|corner| inverse.project_point3(corner));
+
+            // A bounding sphere keeps the size of the cascade constant while the camera rotates, which avoids shimmering
+            let center = corners.iter().sum::<Vec3>() / corners.len() as f32;
+            let radius = corners.iter().map(|corner| corner.distance(center)).fold(0.0f32, f32::max);
+            // A camera with equal near and far planes has a single point as slice, which still needs a valid cascade
+            let radius = ((radius * 16.0).ceil() / 16.0).max(MIN_EXTENT);
+
+            // Moving the center in whole texels keeps shadow edges still while the camera moves
+            let texel_size = radius * 2.0 / resolution;
+            let mut light_center = rotation.transform_point3(center);
+            light_center.x = (light_center.x / texel_size).floor() * texel_size;
+            light_center.y = (light_center.y / texel_size).floor() * texel_size;
+            let center = rotation.inverse().transform_point3(light_center);
+
+            let view = Mat4::look_at_rh(center - direction * radius, center, up);
+            let scene = scene_bounds.transform(&view);
+            let near = (-scene.max.z).min(0.0);
+            let far = (-scene.min.z).max(radius * 2.0);
+            let projection = Mat4::orthographic_rh_gl(-radius, radius, -radius, radius, near, far);
+
+            self.splits[cascade] = split_far;
+            self.matrices[cascade] = projection * view;
+            split_near = split_far;
+        }
+    }
+
+    /// Binds the layer of a cascade, clears its depth and runs the closure, in which the shadow casters are rendered
+    /// with [`CascadedShadowMap::light_space_matrix`] of the cascade. Afterwards the previous framebuffer and viewport
+    /// are restored. Depth testing has to be enabled.
+    pub fn render_cascade<R>(&self, cascade: u32, f: impl FnOnce() -> R) -> Result<R, String> {
+        self.render_texture.attach_array_layer(&self.array, cascade)?;
+
+        let _guard = self.render_texture.bind_scoped();
+        self.render_texture.clear();
+        Ok(f())
+    }
+
+    /// Binds the depth texture array with a depth comparison sampler to a texture unit, for sampling it as
+    /// `sampler2DArrayShadow`.
+    pub fn bind(&self, texture_unit: u32) {
+        self.array.bind(texture_unit);
+        self.compare_sampler.bind(texture_unit);
+    }
+
+    /// Unbinds the depth texture array and the comparison sampler from a texture unit.
+    pub fn unbind(&self, texture_unit: u32) {
+        self.compare_sampler.unbind(texture_unit);
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + texture_unit);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, 0);
+        }
+    }
+
+    /// Binds the cascades to a texture unit and sets the `cascadeShadowMap`, `cascadeMatrices`, `cascadeSplits` and
+    /// `cascadeCount` uniforms of the bound shader, as declared by [`SHADOW_CASCADE_GLSL`].
+    pub fn apply_uniforms(&self, shader: &Shader, texture_unit: u32) {
+        self.bind(texture_unit);
+        shader.set_uniform_texture("cascadeShadowMap", texture_unit);
+        shader.set_uniform_1i("cascadeCount", self.splits.len() as i32);
+        for (cascade, (split, matrix)) in self.splits.iter().zip(&self.matrices).enumerate() {
+            shader.set_uniform_1f(&format!("cascadeSplits[{}]", cascade), *split);
+            shader.set_uniform_matrix_4fv(&format!("cascadeMatrices[{}]", cascade), matrix.to_cols_array().as_ref());
+        }
+    }
+
+    /// Returns the amount of cascades.
+    pub fn cascade_count(&self) -> u32 {
+        self.splits.len() as u32
+    }
+
+    /// Returns the view distance at which a cascade ends.
+    pub fn split(&self, cascade: u32) -> Option<f32> {
+        self.splits.get(cascade as usize).copied()
+    }
+
+    /// Returns the matrix transforming world positions into the clip space of the light for a cascade.
+    pub fn light_space_matrix(&self, cascade: u32) -> Option<Mat4> {
+        self.matrices.get(cascade as usize).copied()
+    }
+
+    /// Returns the depth texture array holding one layer per cascade.
+    pub fn texture_array(&self) -> &TextureArray {
+        &self.array
+    }
+}
+
+/// Internal function returning the sampler settings for hardware depth comparison. Everything outside of the shadow
+/// map is lit, and linear filtering averages four comparisons for slightly smoother edges.
+fn compare_sampler_settings() -> SamplerSettings {
+    SamplerSettings {
+        border_color: [1.0, 1.0, 1.0, 1.0],
+        min_filter: FilterMode::Linear,
+        mag_filter: FilterMode::Linear,
+        compare: Some(CompareFunction::LessOrEqual),
+        ..SamplerSettings::with_wrap(WrapMode::ClampToBorder)
+    }
+}
+
+/// Internal function returning an up vector that is not parallel to the light direction.
+fn up_vector(direction: Vec3) -> Vec3 {
+    if direction.y.abs() > 0.99 {
+        Vec3::Z
+    } else {
+        Vec3::Y
+    }
+}
+
+/// The smallest extent of a light projection, which keeps the matrices of flat bounds (like a single plane) invertible.
+const MIN_EXTENT: f32 = 0.001;
+
+/// Internal function returning the view and orthographic projection of a directional light enclosing the bounds.
+fn directional_matrices(direction: Vec3, bounds: &BoundingBox) -> (Mat4, Mat4) {
+    let direction = direction.normalize_or(Vec3::NEG_Y);
+    let center = bounds.center();
+    let radius = bounds.half_extents().length().max(MIN_EXTENT);
+
+    let view = Mat4::look_at_rh(center - direction * radius, center, up_vector(direction));
+    let light_bounds = bounds.transform(&view);
+    let min = light_bounds.min - Vec3::splat(MIN_EXTENT * 0.5);
+    let max = light_bounds.max.max(min + Vec3::splat(MIN_EXTENT)) + Vec3::splat(MIN_EXTENT * 0.5);
+    let projection = Mat4::orthographic_rh_gl(min.x, max.x, min.y, max.y, -max.z, -min.z);
+
+    (view, projection)
+}
+
+/// Internal function returning the view and perspective projection of a spot light, with the near and far plane
+/// fitted to the part of the bounds within range.
+fn spot_matrices(position: Vec3, direction: Vec3, cone_angle: f32, range: f32, bounds: &BoundingBox) -> (Mat4, Mat4) {
+    let direction = direction.normalize_or(Vec3::NEG_Y);
+    let view = Mat4::look_at_rh(position, position + direction, up_vector(direction));
+
+    let light_bounds = bounds.transform(&view);
+    let far = (-light_bounds.min.z).min(range).max(0.01);
+    let near = (-light_bounds.max.z).max(far * 0.001);
+    // Bounds that are flat along the light direction or out of range would give an empty depth range
+    let near = if far - near >= MIN_EXTENT { near } else { far * 0.001 };
+
+    let projection = Mat4::perspective_rh_gl(cone_angle.clamp(0.01, std::f32::consts::PI - 0.01), 1.0, near, far);
+
+    (view, projection)
+}
+
+/// Internal function returning the view distance at which a cascade ends, blending logarithmic and uniform splits.
+fn split_distance(near: f32, far: f32, lambda: f32, index: usize, count: usize) -> f32 {
+    let fraction = index as f32 / count as f32;
+    let logarithmic = near * (far / near).powf(fraction);
+    let uniform = near + (far - near) * fraction;
+    lambda * logarithmic + (1.0 - lambda) * uniform
+}
+
+/// Internal function returning the corners of the normalized device coordinate cube.
+fn ndc_corners() -> [Vec3; 8] {
+    BoundingBox::new(Vec3::NEG_ONE, Vec3::ONE).corners()
+}
+
+/// GLSL function `float shadowPcf(sampler2DShadow shadowMap, vec4 lightSpacePosition, float bias, int radius)`,
+/// returning how lit a fragment is from 0.0 to 1.0 by averaging the depth comparisons of `(2 * radius + 1)²` texels.
+/// `lightSpacePosition` is the world position transformed by [`ShadowMap::light_space_matrix`]. Insert it into a
+/// fragment shader after the `#version` line.
+pub const SHADOW_PCF_GLSL: &str = r#"
+float shadowPcf(sampler2DShadow shadowMap, vec4 lightSpacePosition, float bias, int radius) {
+    vec3 coords = lightSpacePosition.xyz / lightSpacePosition.w * 0.5 + 0.5;
+    if (coords.z > 1.0) {
+        return 1.0;
+    }
+
+    vec2 texelSize = 1.0 / vec2(textureSize(shadowMap, 0));
+    float lit = 0.0;
+    for (int x = -radius; x <= radius; x++) {
+        for (int y = -radius; y <= radius; y++) {
+            lit += texture(shadowMap, vec3(coords.xy + vec2(x, y) * texelSize, coords.z - bias));
+        }
+    }
+
+    float samples = float((2 * radius + 1) * (2 * radius + 1));
+    return lit / samples;
+}
+"#;
+
+/// GLSL function `float shadowPcss(sampler2DShadow shadowMap, sampler2D depthMap, vec4 lightSpacePosition, float bias,
+/// float lightSize)` for percentage-closer soft shadows, which get softer the further they are from their caster.
+/// `depthMap` is the same shadow map bound without comparison, see [`ShadowMap::bind_depth`], and `lightSize` the
+/// size of the light in shadow map texture coordinates, for example 0.02. Insert it into a fragment shader after the
+/// `#version` line.
+pub const SHADOW_PCSS_GLSL: &str = r#"
+float shadowPcss(sampler2DShadow shadowMap, sampler2D depthMap, vec4 lightSpacePosition, float bias, float lightSize) {
+    vec3 coords = lightSpacePosition.xyz / lightSpacePosition.w * 0.5 + 0.5;
+    if (coords.z > 1.0) {
+        return 1.0;
+    }
+
+    // Blocker search: the average depth of the casters between the fragment and the light
+    float blockerDepth = 0.0;
+    int blockers = 0;
+    for (int x = -2; x <= 2; x++) {
+        for (int y = -2; y <= 2; y++) {
+            float depth = texture(depthMap, coords.xy + vec2(x, y) * lightSize * 0.5).r;
+            if (depth < coords.z - bias) {
+                blockerDepth += depth;
+                blockers++;
+            }
+        }
+    }
+    if (blockers == 0) {
+        return 1.0;
+    }
+    blockerDepth /= float(blockers);
+
+    // The penumbra grows with the distance between the blocker and the receiver
+    vec2 texelSize = 1.0 / vec2(textureSize(shadowMap, 0));
+    float penumbra = max((coords.z - blockerDepth) * lightSize / max(blockerDepth, 0.0001), texelSize.x);
+
+    float lit = 0.0;
+    for (int x = -2; x <= 2; x++) {
+        for (int y = -2; y <= 2; y++) {
+            lit += texture(shadowMap, vec3(coords.xy + vec2(x, y) * penumbra * 0.5, coords.z - bias));
+        }
+    }
+    return lit / 25.0;
+}
+"#;
+
+/// GLSL uniforms and function `float shadowCascaded(vec3 worldPosition, float viewDepth, float bias)` for
+/// [`CascadedShadowMap`], returning how lit a fragment is from 0.0 to 1.0. `viewDepth` is the positive distance of the
+/// fragment along the view direction of the camera, `-viewPosition.z`. The uniforms are set by
+/// [`CascadedShadowMap::apply_uniforms`]. Insert it into a fragment shader after the `#version` line.
+pub const SHADOW_CASCADE_GLSL: &str = r#"
+#define MAX_CASCADES 8
+
+uniform sampler2DArrayShadow cascadeShadowMap;
+uniform mat4 cascadeMatrices[MAX_CASCADES];
+uniform float cascadeSplits[MAX_CASCADES];
+uniform int cascadeCount;
+
+float shadowCascaded(vec3 worldPosition, float viewDepth, float bias) {
+    int cascade = cascadeCount - 1;
+    for (int i = 0; i < cascadeCount; i++) {
+        if (viewDepth < cascadeSplits[i]) {
+            cascade = i;
+            break;
+        }
+    }
+
+    vec4 lightSpacePosition = cascadeMatrices[cascade] * vec4(worldPosition, 1.0);
+    vec3 coords = lightSpacePosition.xyz / lightSpacePosition.w * 0.5 + 0.5;
+    if (coords.z > 1.0) {
+        return 1.0;
+    }
+
+    vec2 texelSize = 1.0 / vec2(textureSize(cascadeShadowMap, 0).xy);
+    float lit = 0.0;
+    for (int x = -1; x <= 1; x++) {
+        for (int y = -1; y <= 1; y++) {
+            lit += texture(cascadeShadowMap, vec4(coords.xy + vec2(x, y) * texelSize, float(cascade), coords.z - bias));
+        }
+    }
+    return lit / 9.0;
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns if the point lies inside of the clip volume after projecting it with the matrices.
+    fn projects_inside(view: Mat4, projection: Mat4, point: Vec3) -> bool {
+        let ndc = (projection * view).project_point3(point);
+        ndc.is_finite() && ndc.abs().cmple(Vec3::splat(1.0 + 1e-4)).all()
+    }
+
+    #[test]
+    fn directional_matrices_enclose_bounds() {
+        let bounds = BoundingBox::new(Vec3::new(-5.0, 0.0, -3.0), Vec3::new(4.0, 2.0, 6.0));
+        for direction in [Vec3::NEG_Y, Vec3::new(1.0, -1.0, 0.5), Vec3::X] {
+            let (view, projection) = directional_matrices(direction, &bounds);
+            assert!(bounds.corners().iter().all(|&corner| projects_inside(view, projection, corner)));
+        }
+    }
+
+    #[test]
+    fn directional_matrices_of_flat_bounds_are_finite() {
+        // A ground plane seen straight from above has no extent along the light direction
+        let plane = BoundingBox::new(Vec3::new(-10.0, 0.0, -10.0), Vec3::new(10.0, 0.0, 10.0));
+        let point = BoundingBox::new(Vec3::ONE, Vec3::ONE);
+
+        for bounds in [plane, point] {
+            let (view, projection) = directional_matrices(Vec3::NEG_Y, &bounds);
+            assert!(projection.is_finite() && (projection * view).determinant() != 0.0);
+            assert!(bounds.corners().iter().all(|&corner| projects_inside(view, projection, corner)));
+        }
+    }
+
+    #[test]
+    fn spot_matrices_fit_depth_range() {
+        let bounds = BoundingBox::new(Vec3::new(-1.0, -8.0, -1.0), Vec3::new(1.0, -2.0, 1.0));
+        let (view, projection) = spot_matrices(Vec3::ZERO, Vec3::NEG_Y, 1.0, 100.0, &bounds);
+
+        assert!(projects_inside(view, projection, Vec3::new(0.0, -2.0, 0.0)));
+        assert!(projects_inside(view, projection, Vec3::new(0.0, -8.0, 0.0)));
+        assert!(!projects_inside(view, projection, Vec3::new(0.0, -9.0, 0.0)));
+
+        // The far plane is limited by the range of the light
+        let (view, projection) = spot_matrices(Vec3::ZERO, Vec3::NEG_Y, 1.0, 5.0, &bounds);
+        assert!(projects_inside(view, projection, Vec3::new(0.0, -4.9, 0.0)));
+        assert!(!projects_inside(view, projection, Vec3::new(0.0, -5.5, 0.0)));
+    }
+
+    #[test]
+    fn spot_matrices_of_flat_or_unreachable_bounds_are_finite() {
+        let flat = BoundingBox::new(Vec3::new(-1.0, -3.0, -1.0), Vec3::new(1.0, -3.0, 1.0));
+        let behind = BoundingBox::new(Vec3::new(-1.0, 2.0, -1.0), Vec3::new(1.0, 3.0, 1.0));
+        let out_of_range = BoundingBox::new(Vec3::new(-1.0, -30.0, -1.0), Vec3::new(1.0, -20.0, 1.0));
+
+        for bounds in [flat, behind, out_of_range] {
+            let (view, projection) = spot_matrices(Vec3::ZERO, Vec3::NEG_Y, 1.0, 10.0, &bounds);
+            assert!(projection.is_finite() && (projection * view).determinant() != 0.0);
+        }
+        let (view, projection) = spot_matrices(Vec3::ZERO, Vec3::NEG_Y, 1.0, 10.0, &flat);
+        assert!(projects_inside(view, projection, Vec3::new(0.0, -3.0, 0.0)));
+    }
+
+    #[test]
+    fn split_distances_cover_camera_range() {
+        let (near, far) = (0.1, 100.0);
+        for lambda in [0.0, 0.5, 1.0] {
+            assert!((split_distance(near, far, lambda, 0, 4) - near).abs() < 1e-5);
+            assert!((split_distance(near, far, lambda, 4, 4) - far).abs() < 1e-3);
+
+            let splits = (0..=4).map(|index| split_distance(near, far, lambda, index, 4)).collect::<Vec<_>>();
+            assert!(splits.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+
+        // Uniform splits are evenly spaced, logarithmic splits have a constant ratio
+        assert!((split_distance(near, far, 0.0, 2, 4) - 50.05).abs() < 1e-3);
+        assert!((split_distance(1.0, 100.0, 1.0, 1, 2) - 10.0).abs() < 1e-4);
+    }
+}
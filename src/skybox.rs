@@ -0,0 +1,111 @@
+extern crate gl;
+extern crate glam;
+
+use glam::Mat4;
+
+use crate::{CubemapTexture, GlWindow, Mesh, Shader};
+
+/// A simple skybox renderer, drawing a cubemap behind everything else in the scene.
+pub struct Skybox {
+    shader: Shader,
+    mesh: Mesh,
+}
+
+impl Skybox {
+    /// Creates a new skybox renderer.
+    pub fn new() -> Self {
+        let shader = Shader::new_from_source(SKYBOX_VERTEX_SHADER, SKYBOX_FRAGMENT_SHADER);
+
+        let mut mesh = Mesh::new();
+
+        let vertices = [
+            -1.0, -1.0, -1.0,
+             1.0, -1.0, -1.0,
+             1.0,  1.0, -1.0,
+            -1.0,  1.0, -1.0,
+            -1.0, -1.0,  1.0,
+             1.0, -1.0,  1.0,
+             1.0,  1.0,  1.0,
+            -1.0,  1.0,  1.0,
+        ];
+
+        // Faces are wound counter-clockwise when seen from inside, as the camera is always inside of the skybox
+        let indices = [
+            0, 1, 2,  0, 2, 3,  // back
+            4, 6, 5,  4, 7, 6,  // front
+            0, 7, 4,  0, 3, 7,  // left
+            1, 6, 2,  1, 5, 6,  // right
+            3, 6, 7,  3, 2, 6,  // top
+            0, 5, 1,  0, 4, 5,  // bottom
+        ];
+
+        mesh.update_vertices(&vertices);
+        mesh.update_indices(&indices);
+        mesh.add_vertex_attributes(&[
+            (0, 3, gl::FLOAT, false), // position
+        ]);
+
+        Skybox { shader, mesh }
+    }
+
+    /// Renders the cubemap as a skybox onto the current bound framebuffer. The translation of the view matrix is ignored,
+    /// so the skybox always stays around the camera. Render it after the opaque objects of the scene, it is only drawn
+    /// where nothing else has been drawn yet.
+    pub fn render(&self, window: &GlWindow, cubemap: &CubemapTexture, view: &Mat4, projection: &Mat4) {
+        let view = Mat4::from_mat3(glam::Mat3::from_mat4(*view));
+
+        unsafe {
+            let mut previous_depth_func = 0;
+            gl::GetIntegerv(gl::DEPTH_FUNC, &mut previous_depth_func);
+
+            // The skybox is drawn at the far plane, which has to pass the depth test against the cleared depth buffer
+            gl::DepthFunc(gl::LEQUAL);
+
+            self.shader.bind_program();
+            cubemap.bind_texture(0);
+            self.shader.set_uniform_texture("skybox", 0);
+            self.shader.set_uniform_matrix_4fv("view", view.to_cols_array().as_ref());
+            self.shader.set_uniform_matrix_4fv("projection", projection.to_cols_array().as_ref());
+
+            window.render_mesh(&self.mesh);
+
+            cubemap.unbind_texture();
+            self.shader.unbind_program();
+
+            gl::DepthFunc(previous_depth_func as u32);
+        }
+    }
+}
+
+impl Default for Skybox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const SKYBOX_VERTEX_SHADER: &str = r#"#version 330 core
+layout(location = 0) in vec3 aPos;
+
+out vec3 TexCoord;
+
+uniform mat4 view;
+uniform mat4 projection;
+
+void main() {
+    TexCoord = aPos;
+    vec4 position = projection * view * vec4(aPos, 1.0);
+    // Setting z to w places the skybox on the far plane after the perspective divide
+    gl_Position = position.xyww;
+}
+"#;
+
+const SKYBOX_FRAGMENT_SHADER: &str = r#"#version 330 core
+in vec3 TexCoord;
+out vec4 FragColor;
+
+uniform samplerCube skybox;
+
+void main() {
+    FragColor = texture(skybox, TexCoord);
+}
+"#;
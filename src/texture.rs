@@ -2,15 +2,19 @@ extern crate gl;
 extern crate image;
 
 use gl::types::*;
-use image::DynamicImage;
+use image::{DynamicImage, ImageFormat, Rgb32FImage, Rgba32FImage, RgbaImage};
 use std::path::Path;
 use std::ptr;
 
+use crate::compressed_texture::{CompressedFormat, CompressedImage};
+use crate::sampler::{mag_filter, min_filter, ParameterTarget, SamplerSettings};
+
 /// Represents an OpenGL texture.
 pub struct Texture {
     pub(crate) id: GLuint,
     pub(crate) width: u32,
     pub(crate) height: u32,
+    pub(crate) format: TextureFormat,
 }
 
 impl Texture {
@@ -20,10 +24,45 @@ impl Texture {
         Self::from_image(&img)
     }
 
-    /// Creates a new texture from an in-memory image.
+    /// Creates a new texture from an image file using the given texture config.
+    pub fn new_from_file_with_config(path: &Path, config: &TextureConfig) -> Result<Self, String> {
+        let img = image::open(path).map_err(|e| e.to_string())?;
+        Self::from_image_with_config(&img, config)
+    }
+
+    /// Creates a new texture from an in-memory image. The texture format is chosen to match the image, so grayscale,
+    /// 16-bit and floating point (HDR) images keep their precision instead of being converted to 8-bit RGBA.
+    /// Grayscale textures are sampled as `(l, l, l, a)` in shaders, just like 8-bit RGBA textures made from them.
     pub fn from_image(img: &DynamicImage) -> Result<Self, String> {
-        let img = img.to_rgba8();
-        let (width, height) = img.dimensions();
+        Self::from_image_with_config(img, &TextureConfig::default())
+    }
+
+    /// Creates a new texture from an in-memory image using the given texture config.
+    pub fn from_image_with_config(img: &DynamicImage, config: &TextureConfig) -> Result<Self, String> {
+        let mut format = config.format.unwrap_or_else(|| TextureFormat::from_image(img));
+        if config.srgb {
+            format = format.to_srgb();
+        }
+
+        let texture = Self::create_from_image(img, format, config.mipmaps)?;
+        texture.set_sampler_settings(&config.sampler);
+
+        if config.format.is_none() && format.channel_count() <= 2 {
+            texture.set_luminance_swizzle();
+        }
+
+        Ok(texture)
+    }
+
+    /// Creates a new texture from an in-memory image, converting the image into the given texture format.
+    pub fn from_image_with_format(img: &DynamicImage, format: TextureFormat) -> Result<Self, String> {
+        Self::create_from_image(img, format, true)
+    }
+
+    /// Internal function to create a texture from an image, optionally generating mipmaps for it.
+    fn create_from_image(img: &DynamicImage, format: TextureFormat, mipmaps: bool) -> Result<Self, String> {
+        let data = format.image_data(img)?;
+        let (width, height) = (img.width(), img.height());
 
         let mut texture_id = 0;
 
@@ -33,22 +72,27 @@ impl Texture {
 
             gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
             gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, format.default_filter() as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, format.default_filter() as i32);
 
+            // Rows of formats with less than 4 bytes per pixel are not 4 byte aligned
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
             gl::TexImage2D(
                 gl::TEXTURE_2D,
                 0,
-                gl::RGBA as i32,
+                format.internal_format() as i32,
                 width as i32,
                 height as i32,
                 0,
-                gl::RGBA,
-                gl::UNSIGNED_BYTE,
-                img.as_ptr() as *const GLvoid,
+                format.pixel_format(),
+                format.pixel_type(),
+                data.as_ptr() as *const GLvoid,
             );
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
 
-            gl::GenerateMipmap(gl::TEXTURE_2D);
+            if mipmaps && !format.is_integer() {
+                gl::GenerateMipmap(gl::TEXTURE_2D);
+            }
 
             gl::BindTexture(gl::TEXTURE_2D, 0);
         }
@@ -57,11 +101,105 @@ impl Texture {
             id: texture_id,
             width,
             height,
+            format,
         })
     }
 
+    /// Creates a new texture from a DDS, KTX or KTX2 file holding block compressed (BC1 to BC7, ETC2) pixels,
+    /// including all mipmap levels stored in the file.
+    pub fn new_from_compressed_file(path: &Path) -> Result<Self, String> {
+        let img = CompressedImage::open(path)?;
+        Self::from_compressed_image(&img, &TextureConfig::default())
+    }
+
+    /// Creates a new texture from block compressed pixels using the given texture config. The format of the config
+    /// is ignored, and mipmaps can not be generated for compressed textures, so disabling mipmaps only uploads the
+    /// full size level while enabling them uploads all levels of the image. Returns an error if the graphics driver
+    /// does not support the compressed format, see [`CompressedFormat::is_supported`].
+    pub fn from_compressed_image(img: &CompressedImage, config: &TextureConfig) -> Result<Self, String> {
+        let compressed_format = if config.srgb { img.format.to_srgb() } else { img.format };
+        if !compressed_format.is_supported() {
+            return Err(format!(
+                "[FerrousGl Error] The {:?} compressed texture format is not supported by the graphics driver",
+                compressed_format
+            ));
+        }
+
+        if img.levels.is_empty() {
+            return Err("[FerrousGl Error] The compressed image has no pixel data".to_string());
+        }
+
+        let level_count = if config.mipmaps { img.levels.len() } else { 1 };
+        let level_size = |level: usize| {
+            let shift = u32::try_from(level).unwrap_or(u32::MAX);
+            (
+                img.width.checked_shr(shift).unwrap_or(0).max(1),
+                img.height.checked_shr(shift).unwrap_or(0).max(1),
+            )
+        };
+        for (level, data) in img.levels.iter().take(level_count).enumerate() {
+            let (width, height) = level_size(level);
+            let expected_length = compressed_format.level_size(width, height)?;
+            if data.len() != expected_length {
+                return Err(format!(
+                    "[FerrousGl Error] Expected {} bytes of {:?} data for mipmap level {}, but got {} bytes",
+                    expected_length,
+                    compressed_format,
+                    level,
+                    data.len()
+                ));
+            }
+        }
+
+        let format = TextureFormat::Compressed(compressed_format);
+        let mut texture_id = 0;
+
+        unsafe {
+            gl::GenTextures(1, &mut texture_id);
+            gl::BindTexture(gl::TEXTURE_2D, texture_id);
+
+            for (level, data) in img.levels.iter().take(level_count).enumerate() {
+                let (width, height) = level_size(level);
+                gl::CompressedTexImage2D(
+                    gl::TEXTURE_2D,
+                    level as i32,
+                    format.internal_format(),
+                    width as i32,
+                    height as i32,
+                    0,
+                    data.len() as i32,
+                    data.as_ptr() as *const GLvoid,
+                );
+            }
+
+            // Files do not always contain the full mipmap chain
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAX_LEVEL, level_count as i32 - 1);
+
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+
+        let texture = Texture {
+            id: texture_id,
+            width: img.width,
+            height: img.height,
+            format,
+        };
+        texture.set_sampler_settings(&config.sampler);
+
+        Ok(texture)
+    }
+
     /// Creates an empty texture with the specified width and height.
     pub fn new_empty(width: u32, height: u32) -> Result<Self, String> {
+        Self::new_empty_with_format(width, height, TextureFormat::Rgba8)
+    }
+
+    /// Creates an empty texture with the specified width, height and texture format.
+    pub fn new_empty_with_format(width: u32, height: u32, format: TextureFormat) -> Result<Self, String> {
+        if format.is_compressed() {
+            return Err(format!("[FerrousGl Error] Can not create an empty {:?} texture", format));
+        }
+
         let mut texture_id = 0;
 
         unsafe {
@@ -71,19 +209,19 @@ impl Texture {
             gl::TexImage2D(
                 gl::TEXTURE_2D,
                 0,
-                gl::RGBA as i32,
+                format.internal_format() as i32,
                 width as i32,
                 height as i32,
                 0,
-                gl::RGBA,
-                gl::UNSIGNED_BYTE,
+                format.pixel_format(),
+                format.pixel_type(),
                 ptr::null(),
             );
 
             gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
             gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, format.default_filter() as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, format.default_filter() as i32);
 
             gl::BindTexture(gl::TEXTURE_2D, 0);
         }
@@ -92,9 +230,405 @@ impl Texture {
             id: texture_id,
             width,
             height,
+            format,
+        })
+    }
+
+    /// Creates an empty texture with the specified width and height using the given texture config. The format defaults
+    /// to [`TextureFormat::Rgba8`]. If mipmaps are enabled, storage for all mipmap levels is allocated, which can be
+    /// filled with [`Texture::generate_mipmaps`] after rendering into the texture or with [`Texture::upload_mip_level`].
+    pub fn new_empty_with_config(width: u32, height: u32, config: &TextureConfig) -> Result<Self, String> {
+        let mut format = config.format.unwrap_or(TextureFormat::Rgba8);
+        if config.srgb {
+            format = format.to_srgb();
+        }
+
+        let texture = Self::new_empty_with_format(width, height, format)?;
+        texture.set_sampler_settings(&config.sampler);
+
+        if config.mipmaps {
+            texture.with_binding(|| unsafe {
+                for level in 1..Self::full_mip_level_count(width, height) {
+                    gl::TexImage2D(
+                        gl::TEXTURE_2D,
+                        level as i32,
+                        format.internal_format() as i32,
+                        (width >> level).max(1) as i32,
+                        (height >> level).max(1) as i32,
+                        0,
+                        format.pixel_format(),
+                        format.pixel_type(),
+                        ptr::null(),
+                    );
+                }
+            });
+        }
+
+        Ok(texture)
+    }
+
+    /// Returns the amount of mipmap levels a full mipmap chain of a texture with the given size has.
+    pub fn full_mip_level_count(width: u32, height: u32) -> u32 {
+        32 - width.max(height).max(1).leading_zeros()
+    }
+
+    /// Generates all mipmap levels from the base level of the texture. This is useful after rendering into a
+    /// [`crate::RenderTexture`] or updating the texture, so it can be sampled with mipmapping.
+    pub fn generate_mipmaps(&self) -> Result<(), String> {
+        if self.format.is_integer() || self.format.is_depth() || self.format.is_compressed() {
+            return Err(format!("[FerrousGl Error] Can not generate mipmaps for a {:?} texture", self.format));
+        }
+
+        self.with_binding(|| unsafe {
+            gl::GenerateMipmap(gl::TEXTURE_2D);
+        });
+
+        Ok(())
+    }
+
+    /// Uploads the pixels of a single mipmap level, for example from precomputed mipmaps. The size of level `n` is the
+    /// size of the texture divided by `2^n`, rounded down but at least one pixel. The data has the same layout as for
+    /// [`Texture::update_region`]. If not all levels are uploaded, see [`Texture::set_mip_level_range`].
+    pub fn upload_mip_level(&self, level: u32, data: &[u8]) -> Result<(), String> {
+        if self.format.is_compressed() {
+            return Err(format!("[FerrousGl Error] Can not upload uncompressed pixels to a {:?} texture", self.format));
+        }
+        if level >= Self::full_mip_level_count(self.width, self.height) {
+            return Err(format!("[FerrousGl Error] The texture has no mipmap level {}", level));
+        }
+
+        let (width, height) = ((self.width >> level).max(1), (self.height >> level).max(1));
+        let expected_length = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|pixels| pixels.checked_mul(self.format.bytes_per_pixel()))
+            .ok_or_else(|| format!("[FerrousGl Error] Mipmap level {} of the texture is too large", level))?;
+        if data.len() != expected_length {
+            return Err(format!(
+                "[FerrousGl Error] Expected {} bytes of {:?} pixel data for mipmap level {}, but got {} bytes",
+                expected_length, self.format, level, data.len()
+            ));
+        }
+
+        self.with_binding(|| unsafe {
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                level as i32,
+                self.format.internal_format() as i32,
+                width as i32,
+                height as i32,
+                0,
+                self.format.pixel_format(),
+                self.format.pixel_type(),
+                data.as_ptr() as *const GLvoid,
+            );
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+        });
+
+        Ok(())
+    }
+
+    /// Limits which mipmap levels are used when sampling the texture. A texture is only complete, and therefore only
+    /// sampled correctly, if all levels from `base_level` to `max_level` are uploaded.
+    pub fn set_mip_level_range(&self, base_level: u32, max_level: u32) {
+        self.with_binding(|| unsafe {
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_BASE_LEVEL, base_level as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAX_LEVEL, max_level as i32);
+        });
+    }
+
+    /// Returns the amount of mipmap levels which currently have storage, including the base level.
+    pub fn mip_level_count(&self) -> u32 {
+        self.with_binding(|| unsafe {
+            (0..Self::full_mip_level_count(self.width, self.height))
+                .take_while(|&level| {
+                    let mut width = 0;
+                    gl::GetTexLevelParameteriv(gl::TEXTURE_2D, level as i32, gl::TEXTURE_WIDTH, &mut width);
+                    width > 0
+                })
+                .count() as u32
+        })
+    }
+
+    /// Internal function to make single and dual channel textures be sampled as `(r, r, r, g)` instead of `(r, g, 0, 1)`.
+    fn set_luminance_swizzle(&self) {
+        let alpha = match self.format.channel_count() {
+            2 => gl::GREEN,
+            _ => gl::ONE,
+        };
+        let swizzle = [gl::RED as i32, gl::RED as i32, gl::RED as i32, alpha as i32];
+
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, self.id);
+            gl::TexParameteriv(gl::TEXTURE_2D, gl::TEXTURE_SWIZZLE_RGBA, swizzle.as_ptr());
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+    }
+
+    /// Internal function returning if the texture is sampled as grayscale, see [`Texture::set_luminance_swizzle`].
+    /// Reading pixels back ignores the swizzle, so grayscale textures have to be expanded after reading them.
+    fn has_luminance_swizzle(&self) -> bool {
+        if self.format.channel_count() > 2 {
+            return false;
+        }
+
+        self.with_binding(|| unsafe {
+            let mut green = 0;
+            gl::GetTexParameteriv(gl::TEXTURE_2D, gl::TEXTURE_SWIZZLE_G, &mut green);
+            green as u32 == gl::RED
         })
     }
 
+    /// Sets how the texture is sampled in shaders, such as wrapping, filtering, anisotropy and depth comparison.
+    /// The texture does not have to be bound for this. Integer textures are always sampled with nearest filtering.
+    pub fn set_sampler_settings(&self, settings: &SamplerSettings) {
+        let mut settings = *settings;
+        if self.format.is_integer() {
+            settings.min_filter = FilterMode::Nearest;
+            settings.mag_filter = FilterMode::Nearest;
+            settings.mipmap = MipmapType::None;
+        }
+
+        self.with_binding(|| settings.apply(ParameterTarget::Texture(gl::TEXTURE_2D)));
+    }
+
+    /// Internal function to run OpenGL calls with the texture bound, restoring the previously bound texture afterwards.
+    pub(crate) fn with_binding<R>(&self, f: impl FnOnce() -> R) -> R {
+        unsafe {
+            let mut previous_texture = 0;
+            gl::GetIntegerv(gl::TEXTURE_BINDING_2D, &mut previous_texture);
+            gl::BindTexture(gl::TEXTURE_2D, self.id);
+
+            let result = f();
+
+            gl::BindTexture(gl::TEXTURE_2D, previous_texture as GLuint);
+            result
+        }
+    }
+
+    /// Writes pixels into a region of the texture, starting at the bottom left corner `(x, y)`.
+    /// The data has to be laid out in rows as described by [`TextureFormat::pixel_format`] and [`TextureFormat::pixel_type`]
+    /// of the textures format, without any padding between rows.
+    pub fn update_region(&self, x: u32, y: u32, width: u32, height: u32, data: &[u8]) -> Result<(), String> {
+        self.check_region(x, y, width, height, data.len())?;
+        self.upload_region(x, y, width, height, data.as_ptr() as *const GLvoid);
+        Ok(())
+    }
+
+    /// Writes an image into a region of the texture, starting at the bottom left corner `(x, y)`.
+    /// The image is converted into the textures format first.
+    pub fn update_region_from_image(&self, x: u32, y: u32, img: &DynamicImage) -> Result<(), String> {
+        let data = self.format.image_data(img)?;
+        self.update_region(x, y, img.width(), img.height(), &data)
+    }
+
+    /// Internal function to check if a region lies inside of the texture, without overflowing for huge regions.
+    pub(crate) fn check_bounds(&self, x: u32, y: u32, width: u32, height: u32) -> Result<(), String> {
+        let inside = |start: u32, size: u32, limit: u32| start.checked_add(size).is_some_and(|end| end <= limit);
+        if !inside(x, width, self.width) || !inside(y, height, self.height) {
+            return Err(format!(
+                "[FerrousGl Error] Region {}x{} at ({}, {}) is outside of the {}x{} texture",
+                width, height, x, y, self.width, self.height
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Internal function returning the amount of bytes a region of pixels takes in the textures format. The region
+    /// has to be checked with [`Texture::check_bounds`] first, so the size can not overflow.
+    pub(crate) fn region_length(&self, width: u32, height: u32) -> usize {
+        width as usize * height as usize * self.format.bytes_per_pixel()
+    }
+
+    /// Internal function to check if a region lies inside of the texture and the data has the right size for it.
+    pub(crate) fn check_region(&self, x: u32, y: u32, width: u32, height: u32, data_length: usize) -> Result<(), String> {
+        if self.format.is_compressed() {
+            return Err(format!("[FerrousGl Error] Can not upload uncompressed pixels to a {:?} texture", self.format));
+        }
+        self.check_bounds(x, y, width, height)?;
+
+        let expected_length = self.region_length(width, height);
+        if data_length != expected_length {
+            return Err(format!(
+                "[FerrousGl Error] Expected {} bytes of {:?} pixel data, but got {} bytes",
+                expected_length, self.format, data_length
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Internal function to upload pixels into a region of the texture. `pixels` is either a pointer to
+    /// the data or an offset into the currently bound pixel unpack buffer.
+    pub(crate) fn upload_region(&self, x: u32, y: u32, width: u32, height: u32, pixels: *const GLvoid) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, self.id);
+
+            // Rows of formats with less than 4 bytes per pixel or odd widths are not 4 byte aligned
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TexSubImage2D(
+                gl::TEXTURE_2D,
+                0,
+                x as i32,
+                y as i32,
+                width as i32,
+                height as i32,
+                self.format.pixel_format(),
+                self.format.pixel_type(),
+                pixels,
+            );
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+    }
+
+    /// Reads the pixels of the texture back from the GPU as an 8-bit RGBA image. Floating point values are clamped
+    /// to `[0, 1]`, sRGB textures are returned still encoded. Grayscale textures made from images are returned as
+    /// `(l, l, l, a)`, the way they are sampled in shaders. Note that this stalls until the GPU has finished rendering,
+    /// see [`crate::PixelReadbackBuffer`] for reading pixels back asynchronously.
+    pub fn read_pixels(&self) -> Result<RgbaImage, String> {
+        self.read_region(0, 0, self.width, self.height)
+    }
+
+    /// Reads a region of the texture starting at the bottom left corner `(x, y)` back from the GPU as an 8-bit RGBA image.
+    pub fn read_region(&self, x: u32, y: u32, width: u32, height: u32) -> Result<RgbaImage, String> {
+        self.check_color_readback()?;
+        self.check_bounds(x, y, width, height)?;
+
+        let mut buffer = vec![0u8; width as usize * height as usize * 4];
+        self.read_region_into(x, y, width, height, gl::RGBA, gl::UNSIGNED_BYTE, buffer.as_mut_ptr() as *mut GLvoid)?;
+        if self.has_luminance_swizzle() {
+            expand_luminance(&mut buffer, self.format.channel_count());
+        }
+
+        RgbaImage::from_raw(width, height, buffer)
+            .ok_or_else(|| "[FerrousGl Error] Failed to create image from texture data".to_string())
+    }
+
+    /// Reads the pixels of the texture back from the GPU as a 32-bit float RGBA image, keeping the full
+    /// precision and range of floating point textures.
+    pub fn read_pixels_f32(&self) -> Result<Rgba32FImage, String> {
+        self.check_color_readback()?;
+
+        let mut buffer = vec![0f32; self.width as usize * self.height as usize * 4];
+        self.read_region_into(0, 0, self.width, self.height, gl::RGBA, gl::FLOAT, buffer.as_mut_ptr() as *mut GLvoid)?;
+        if self.has_luminance_swizzle() {
+            expand_luminance(&mut buffer, self.format.channel_count());
+        }
+
+        Rgba32FImage::from_raw(self.width, self.height, buffer)
+            .ok_or_else(|| "[FerrousGl Error] Failed to create image from texture data".to_string())
+    }
+
+    /// Reads the values of a depth texture back from the GPU, one float in the range `[0, 1]` per pixel.
+    pub fn read_depth(&self) -> Result<Vec<f32>, String> {
+        if !self.format.is_depth() {
+            return Err(format!("[FerrousGl Error] Can not read depth values from a {:?} texture", self.format));
+        }
+
+        let mut buffer = vec![0f32; self.width as usize * self.height as usize];
+        self.read_region_into(0, 0, self.width, self.height, gl::DEPTH_COMPONENT, gl::FLOAT, buffer.as_mut_ptr() as *mut GLvoid)?;
+
+        Ok(buffer)
+    }
+
+    /// Reads a region of the texture starting at the bottom left corner `(x, y)` back from the GPU in the textures own
+    /// format, laid out as described by [`TextureFormat::pixel_format`] and [`TextureFormat::pixel_type`].
+    /// This is the counterpart to [`Texture::update_region`] and works for every uncompressed texture format.
+    /// Compressed textures can not be rendered into, so they can not be read back this way and return an error.
+    pub fn read_region_data(&self, x: u32, y: u32, width: u32, height: u32) -> Result<Vec<u8>, String> {
+        if self.format.is_compressed() {
+            return Err(format!("[FerrousGl Error] Can not read back pixels of a {:?} texture", self.format));
+        }
+        self.check_bounds(x, y, width, height)?;
+
+        let mut buffer = vec![0u8; self.region_length(width, height)];
+        self.read_region_into(
+            x,
+            y,
+            width,
+            height,
+            self.format.pixel_format(),
+            self.format.pixel_type(),
+            buffer.as_mut_ptr() as *mut GLvoid,
+        )?;
+
+        Ok(buffer)
+    }
+
+    /// Internal function to check if the texture can be read back as normalized or float colors.
+    fn check_color_readback(&self) -> Result<(), String> {
+        if self.format.is_depth() || self.format.is_integer() {
+            return Err(format!(
+                "[FerrousGl Error] Can not read colors from a {:?} texture, use read_depth or read_region_data instead",
+                self.format
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Internal function to read a region of the texture by attaching it to a temporary framebuffer. `pixels` is either
+    /// a pointer to memory large enough for the region or an offset into the currently bound pixel pack buffer.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn read_region_into(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        pixel_format: GLenum,
+        pixel_type: GLenum,
+        pixels: *mut GLvoid,
+    ) -> Result<(), String> {
+        self.check_bounds(x, y, width, height)?;
+
+        unsafe {
+            let (mut previous_read_framebuffer, mut previous_draw_framebuffer) = (0, 0);
+            gl::GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut previous_read_framebuffer);
+            gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut previous_draw_framebuffer);
+
+            let mut framebuffer_id = 0;
+            gl::GenFramebuffers(1, &mut framebuffer_id);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer_id);
+
+            let attachment = if self.format.has_stencil() {
+                gl::DEPTH_STENCIL_ATTACHMENT
+            } else if self.format.is_depth() {
+                gl::DEPTH_ATTACHMENT
+            } else {
+                gl::COLOR_ATTACHMENT0
+            };
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER, attachment, gl::TEXTURE_2D, self.id, 0);
+            if self.format.is_depth() {
+                // Without a color attachment, the draw buffer would make the framebuffer incomplete
+                gl::DrawBuffer(gl::NONE);
+                gl::ReadBuffer(gl::NONE);
+            } else {
+                gl::ReadBuffer(gl::COLOR_ATTACHMENT0);
+            }
+
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            if status == gl::FRAMEBUFFER_COMPLETE {
+                gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+                gl::ReadPixels(x as i32, y as i32, width as i32, height as i32, pixel_format, pixel_type, pixels);
+                gl::PixelStorei(gl::PACK_ALIGNMENT, 4);
+            }
+
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, previous_read_framebuffer as GLuint);
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, previous_draw_framebuffer as GLuint);
+            gl::DeleteFramebuffers(1, &framebuffer_id);
+
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                return Err(format!("[FerrousGl Error] Can not read back a {:?} texture", self.format));
+            }
+        }
+
+        Ok(())
+    }
+
     /// Binds the texture to a specific texture unit which can be used to set a uniform texture.
     pub fn bind(&self, texture_unit: u32) {
         unsafe {
@@ -111,57 +645,15 @@ impl Texture {
     }
 
     /// Sets the preferred Texture Mipmap Type or Texture Filtering Mode such as None, Linear and Nearest.
-    /// Do this after binding a texture, otherwise it will not take effect.
+    /// The texture does not have to be bound for this. To sample the same texture with different filtering, see [`crate::Sampler`].
+    /// For backwards compatibility, the base filter selects between mipmap levels and the mipmap type within a level
+    /// when both are combined. [`SamplerSettings`] uses them the other way around, as their names suggest.
     pub fn set_mipmap_and_filtering(&self, mipmap_type: MipmapType, base_filter: FilterMode) {
-        unsafe {
-            // Set minification filter based on both mipmap type and base filter
-            match (mipmap_type, base_filter) {
-                (MipmapType::None, FilterMode::Linear) => {
-                    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-                }
-                (MipmapType::None, FilterMode::Nearest) => {
-                    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32);
-                }
-                (MipmapType::Nearest, FilterMode::Linear) => {
-                    gl::TexParameteri(
-                        gl::TEXTURE_2D,
-                        gl::TEXTURE_MIN_FILTER,
-                        gl::NEAREST_MIPMAP_LINEAR as i32,
-                    );
-                }
-                (MipmapType::Nearest, FilterMode::Nearest) => {
-                    gl::TexParameteri(
-                        gl::TEXTURE_2D,
-                        gl::TEXTURE_MIN_FILTER,
-                        gl::NEAREST_MIPMAP_NEAREST as i32,
-                    );
-                }
-                (MipmapType::Linear, FilterMode::Linear) => {
-                    gl::TexParameteri(
-                        gl::TEXTURE_2D,
-                        gl::TEXTURE_MIN_FILTER,
-                        gl::LINEAR_MIPMAP_LINEAR as i32,
-                    );
-                }
-                (MipmapType::Linear, FilterMode::Nearest) => {
-                    gl::TexParameteri(
-                        gl::TEXTURE_2D,
-                        gl::TEXTURE_MIN_FILTER,
-                        gl::LINEAR_MIPMAP_NEAREST as i32,
-                    );
-                }
-            }
-
-            // Set magnification filter (mipmaps don't affect magnification)
-            match base_filter {
-                FilterMode::Linear => {
-                    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
-                }
-                FilterMode::Nearest => {
-                    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
-                }
-            }
-        }
+        self.with_binding(|| unsafe {
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, legacy_min_filter(mipmap_type, base_filter) as i32);
+            // Mipmaps don't affect magnification
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, mag_filter(base_filter) as i32);
+        });
     }
 
     /// Returns the width of the texture.
@@ -174,84 +666,75 @@ impl Texture {
         self.height
     }
 
-    pub fn save_to_file(&self, path: &Path) -> Result<(), String> {
-    // Bind the texture
-    unsafe {
-        gl::BindTexture(gl::TEXTURE_2D, self.id);
-    }
-
-    // First try to read as RGBA texture
-    let mut buffer = vec![0u8; (self.width * self.height * 4) as usize];
-    
-    unsafe {
-        gl::GetTexImage(
-            gl::TEXTURE_2D,
-            0,
-            gl::RGBA,
-            gl::UNSIGNED_BYTE,
-            buffer.as_mut_ptr() as *mut GLvoid,
-        );
+    /// Returns the format the texture is stored in on the GPU.
+    pub fn format(&self) -> TextureFormat {
+        self.format
     }
 
-    // Check if we got meaningful RGBA data (not all zeros)
-    let is_depth_texture = buffer.iter().all(|&x| x == 0);
-    
-    if is_depth_texture {
-        // If it's a depth texture, read it as depth data
-        let mut depth_buffer = vec![0f32; (self.width * self.height) as usize];
-        
-        unsafe {
-            gl::GetTexImage(
-                gl::TEXTURE_2D,
-                0,
-                gl::DEPTH_COMPONENT,
-                gl::FLOAT,
-                depth_buffer.as_mut_ptr() as *mut GLvoid,
-            );
-            
-            // Unbind the texture
-            gl::BindTexture(gl::TEXTURE_2D, 0);
+    /// Saves the texture to an image file, the image format is chosen based on the file extension.
+    /// Floating point textures hold linear colors and are encoded to sRGB, sRGB textures are saved as they are.
+    /// Grayscale textures made from images are saved with the gray value in every color channel, like
+    /// [`Texture::read_pixels`] returns them.
+    /// Depth textures are saved as a red ramp, see [`Texture::save_depth_to_file`] for other visualizations.
+    pub fn save_to_file(&self, path: &Path) -> Result<(), String> {
+        if self.format.is_depth() {
+            return self.save_depth_to_file(path, DepthVisualization::RedRamp);
         }
 
-        // Convert depth values to red-black gradient
-        let mut image_buffer = Vec::with_capacity((self.width * self.height * 4) as usize);
-        
-        for depth in depth_buffer {
-            // Normalize depth value (assuming it's in [0,1] range)
-            let depth = depth.clamp(0.0, 1.0);
-            
-            // Convert to red-black gradient
-            image_buffer.push((depth * 255.0) as u8); // R
-            image_buffer.push(0);                     // G
-            image_buffer.push(0);                     // B
-            image_buffer.push(255);                   // A
-        }
-
-        // Create and save the image
-        match image::RgbaImage::from_raw(self.width, self.height, image_buffer) {
-            Some(image) => {
-                image.save(path).map_err(|e| e.to_string())?;
-                Ok(())
-            }
-            None => Err("Failed to create image from depth texture data".to_string()),
-        }
-    } else {
-        // Regular RGBA texture
-        unsafe {
-            // Unbind the texture
-            gl::BindTexture(gl::TEXTURE_2D, 0);
+        let image = if self.format.is_float() {
+            let pixels = self.read_pixels_f32()?;
+            let buffer = pixels
+                .into_raw()
+                .into_iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    let value = if index % 4 == 3 { value } else { linear_to_srgb(value) };
+                    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+                })
+                .collect();
+
+            RgbaImage::from_raw(self.width, self.height, buffer)
+                .ok_or_else(|| "[FerrousGl Error] Failed to create image from texture data".to_string())?
+        } else {
+            self.read_pixels()?
+        };
+
+        image.save(path).map_err(|e| e.to_string())
+    }
+
+    /// Saves a depth texture to an image file using the given visualization.
+    pub fn save_depth_to_file(&self, path: &Path, visualization: DepthVisualization) -> Result<(), String> {
+        let depth_buffer = self.read_depth()?;
+
+        let image_buffer = depth_buffer
+            .into_iter()
+            .flat_map(|depth| visualization.color(depth))
+            .collect();
+
+        match RgbaImage::from_raw(self.width, self.height, image_buffer) {
+            Some(image) => image.save(path).map_err(|e| e.to_string()),
+            None => Err("[FerrousGl Error] Failed to create image from depth texture data".to_string()),
         }
+    }
 
-        match image::RgbaImage::from_raw(self.width, self.height, buffer) {
-            Some(image) => {
-                image.save(path).map_err(|e| e.to_string())?;
-                Ok(())
-            }
-            None => Err("Failed to create image from texture data".to_string()),
+    /// Saves the raw values of a depth texture to an OpenEXR file without any loss of precision.
+    /// Every channel of the file holds the depth value.
+    pub fn save_depth_to_exr(&self, path: &Path) -> Result<(), String> {
+        let depth_buffer = self.read_depth()?;
+
+        let image_buffer = depth_buffer
+            .into_iter()
+            .flat_map(|depth| [depth, depth, depth])
+            .collect();
+
+        match Rgb32FImage::from_raw(self.width, self.height, image_buffer) {
+            Some(image) => image
+                .save_with_format(path, ImageFormat::OpenExr)
+                .map_err(|e| e.to_string()),
+            None => Err("[FerrousGl Error] Failed to create image from depth texture data".to_string()),
         }
     }
 }
-}
 
 impl Drop for Texture {
     /// Cleans up the texture when it goes out of scope.
@@ -274,3 +757,411 @@ pub enum FilterMode {
     Linear,
     Nearest,
 }
+
+/// Enum representing the ways depth values can be turned into colors when saving depth textures.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DepthVisualization {
+    /// Depth values are written into the red channel as they are.
+    RedRamp,
+    /// Depth values are written into all color channels as they are.
+    Grayscale,
+    /// Depth values of a perspective projection are converted back to linear distances between the near and far
+    /// plane before being written into all color channels. Without this most of the image appears white.
+    Linearized { near: f32, far: f32 },
+}
+
+impl DepthVisualization {
+    /// Returns the RGBA color a depth value in the range `[0, 1]` is visualized as.
+    pub fn color(self, depth: f32) -> [u8; 4] {
+        let depth = depth.clamp(0.0, 1.0);
+
+        match self {
+            DepthVisualization::RedRamp => [(depth * 255.0) as u8, 0, 0, 255],
+            DepthVisualization::Grayscale => {
+                let value = (depth * 255.0) as u8;
+                [value, value, value, 255]
+            }
+            DepthVisualization::Linearized { near, far } => {
+                let value = (linearize_depth(depth, near, far).clamp(0.0, 1.0) * 255.0) as u8;
+                [value, value, value, 255]
+            }
+        }
+    }
+}
+
+/// Converts a depth value of a perspective projection back to the linear distance between the near
+/// and far plane, where `0.0` lies on the near plane and `1.0` on the far plane.
+pub fn linearize_depth(depth: f32, near: f32, far: f32) -> f32 {
+    let ndc_depth = depth * 2.0 - 1.0;
+    let distance = (2.0 * near * far) / (far + near - ndc_depth * (far - near));
+    (distance - near) / (far - near)
+}
+
+/// Struct to more easily allow setting texture features when creating a texture.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextureConfig {
+    /// The format to store the texture in. `None` picks the format matching the image.
+    pub format: Option<TextureFormat>,
+    /// Whether the image holds sRGB encoded colors, which is the case for most color textures such as albedo maps.
+    /// sRGB textures are decoded to linear colors when sampled in shaders. Data textures such as normal maps,
+    /// roughness maps or height maps should not be marked as sRGB. Formats without an sRGB variant are not affected.
+    pub srgb: bool,
+    /// How the texture is sampled in shaders, can be changed later with [`Texture::set_sampler_settings`].
+    pub sampler: SamplerSettings,
+    /// Whether mipmaps are generated when creating a texture from an image, or allocated when creating an empty texture.
+    /// Note that mipmaps are only used when sampling if [`SamplerSettings::mipmap`] is set.
+    pub mipmaps: bool,
+}
+
+impl Default for TextureConfig {
+    fn default() -> Self {
+        Self {
+            format: None,
+            srgb: false,
+            sampler: SamplerSettings::default(),
+            mipmaps: true,
+        }
+    }
+}
+
+/// Enum representing the formats a texture can be stored in on the GPU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureFormat {
+    R8,
+    Rg8,
+    Rgb8,
+    Rgba8,
+    Srgb8,
+    Srgb8Alpha8,
+    R16,
+    Rgba16,
+    R16F,
+    Rg16F,
+    Rgba16F,
+    R32F,
+    Rg32F,
+    Rgba32F,
+    R32Ui,
+    R11G11B10F,
+    Depth16,
+    Depth24,
+    Depth32F,
+    Depth24Stencil8,
+    Depth32FStencil8,
+    /// A block compressed format, see [`Texture::from_compressed_image`].
+    Compressed(CompressedFormat),
+}
+
+impl TextureFormat {
+    /// Returns the texture format which stores an image without losing precision or channels.
+    pub fn from_image(img: &DynamicImage) -> Self {
+        match img {
+            DynamicImage::ImageLuma8(_) => TextureFormat::R8,
+            DynamicImage::ImageLumaA8(_) => TextureFormat::Rg8,
+            DynamicImage::ImageLuma16(_) => TextureFormat::R16,
+            DynamicImage::ImageLumaA16(_) | DynamicImage::ImageRgb16(_) | DynamicImage::ImageRgba16(_) => {
+                TextureFormat::Rgba16
+            }
+            DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) => TextureFormat::Rgba32F,
+            _ => TextureFormat::Rgba8,
+        }
+    }
+
+    /// Returns the sRGB variant of an 8-bit color format. Single and dual channel formats have no sRGB variant
+    /// and are expanded to [`TextureFormat::Srgb8Alpha8`], all other formats are returned unchanged.
+    pub fn to_srgb(self) -> Self {
+        match self {
+            TextureFormat::Rgb8 => TextureFormat::Srgb8,
+            TextureFormat::R8 | TextureFormat::Rg8 | TextureFormat::Rgba8 => TextureFormat::Srgb8Alpha8,
+            TextureFormat::Compressed(format) => TextureFormat::Compressed(format.to_srgb()),
+            _ => self,
+        }
+    }
+
+    /// Returns the linear variant of an sRGB format, all other formats are returned unchanged.
+    pub fn to_linear(self) -> Self {
+        match self {
+            TextureFormat::Srgb8 => TextureFormat::Rgb8,
+            TextureFormat::Srgb8Alpha8 => TextureFormat::Rgba8,
+            TextureFormat::Compressed(format) => TextureFormat::Compressed(format.to_linear()),
+            _ => self,
+        }
+    }
+
+    /// Returns the OpenGL internal format, which is how the texture is stored on the GPU.
+    pub fn internal_format(self) -> GLenum {
+        match self {
+            TextureFormat::R8 => gl::R8,
+            TextureFormat::Rg8 => gl::RG8,
+            TextureFormat::Rgb8 => gl::RGB8,
+            TextureFormat::Rgba8 => gl::RGBA8,
+            TextureFormat::Srgb8 => gl::SRGB8,
+            TextureFormat::Srgb8Alpha8 => gl::SRGB8_ALPHA8,
+            TextureFormat::R16 => gl::R16,
+            TextureFormat::Rgba16 => gl::RGBA16,
+            TextureFormat::R16F => gl::R16F,
+            TextureFormat::Rg16F => gl::RG16F,
+            TextureFormat::Rgba16F => gl::RGBA16F,
+            TextureFormat::R32F => gl::R32F,
+            TextureFormat::Rg32F => gl::RG32F,
+            TextureFormat::Rgba32F => gl::RGBA32F,
+            TextureFormat::R32Ui => gl::R32UI,
+            TextureFormat::R11G11B10F => gl::R11F_G11F_B10F,
+            TextureFormat::Depth16 => gl::DEPTH_COMPONENT16,
+            TextureFormat::Depth24 => gl::DEPTH_COMPONENT24,
+            TextureFormat::Depth32F => gl::DEPTH_COMPONENT32F,
+            TextureFormat::Depth24Stencil8 => gl::DEPTH24_STENCIL8,
+            TextureFormat::Depth32FStencil8 => gl::DEPTH32F_STENCIL8,
+            TextureFormat::Compressed(format) => format.internal_format(),
+        }
+    }
+
+    /// Returns the OpenGL pixel format of the data used for uploading to and reading from a texture.
+    pub fn pixel_format(self) -> GLenum {
+        match self {
+            TextureFormat::R8 | TextureFormat::R16 | TextureFormat::R16F | TextureFormat::R32F => gl::RED,
+            TextureFormat::Rg8 | TextureFormat::Rg16F | TextureFormat::Rg32F => gl::RG,
+            TextureFormat::Rgb8 | TextureFormat::Srgb8 | TextureFormat::R11G11B10F => gl::RGB,
+            TextureFormat::Rgba8
+            | TextureFormat::Srgb8Alpha8
+            | TextureFormat::Rgba16
+            | TextureFormat::Rgba16F
+            | TextureFormat::Rgba32F => gl::RGBA,
+            TextureFormat::R32Ui => gl::RED_INTEGER,
+            TextureFormat::Depth16 | TextureFormat::Depth24 | TextureFormat::Depth32F => gl::DEPTH_COMPONENT,
+            TextureFormat::Depth24Stencil8 | TextureFormat::Depth32FStencil8 => gl::DEPTH_STENCIL,
+            // Compressed textures are decompressed when read back
+            TextureFormat::Compressed(format) => match format.channel_count() {
+                1 => gl::RED,
+                2 => gl::RG,
+                3 => gl::RGB,
+                _ => gl::RGBA,
+            },
+        }
+    }
+
+    /// Returns the OpenGL data type of the data used for uploading to and reading from a texture.
+    /// Half float formats are uploaded as 32-bit floats and converted by the driver.
+    pub fn pixel_type(self) -> GLenum {
+        match self {
+            TextureFormat::R8
+            | TextureFormat::Rg8
+            | TextureFormat::Rgb8
+            | TextureFormat::Rgba8
+            | TextureFormat::Srgb8
+            | TextureFormat::Srgb8Alpha8 => gl::UNSIGNED_BYTE,
+            TextureFormat::R16 | TextureFormat::Rgba16 | TextureFormat::Depth16 => gl::UNSIGNED_SHORT,
+            TextureFormat::R16F
+            | TextureFormat::Rg16F
+            | TextureFormat::Rgba16F
+            | TextureFormat::R32F
+            | TextureFormat::Rg32F
+            | TextureFormat::Rgba32F
+            | TextureFormat::R11G11B10F
+            | TextureFormat::Depth32F => gl::FLOAT,
+            TextureFormat::R32Ui | TextureFormat::Depth24 => gl::UNSIGNED_INT,
+            TextureFormat::Depth24Stencil8 => gl::UNSIGNED_INT_24_8,
+            TextureFormat::Depth32FStencil8 => gl::FLOAT_32_UNSIGNED_INT_24_8_REV,
+            TextureFormat::Compressed(format) if format.is_float() => gl::FLOAT,
+            TextureFormat::Compressed(_) => gl::UNSIGNED_BYTE,
+        }
+    }
+
+    /// Returns the amount of channels of the format.
+    pub fn channel_count(self) -> usize {
+        match self.pixel_format() {
+            gl::RG | gl::DEPTH_STENCIL => 2,
+            gl::RGB => 3,
+            gl::RGBA => 4,
+            _ => 1,
+        }
+    }
+
+    /// Returns the amount of bytes a single pixel takes up in the data used for uploading to and reading from a texture.
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            TextureFormat::Depth24Stencil8 => 4,
+            TextureFormat::Depth32FStencil8 => 8,
+            _ => {
+                let channel_size = match self.pixel_type() {
+                    gl::UNSIGNED_BYTE => 1,
+                    gl::UNSIGNED_SHORT => 2,
+                    _ => 4,
+                };
+                self.channel_count() * channel_size
+            }
+        }
+    }
+
+    /// Returns if the format stores depth values.
+    pub fn is_depth(self) -> bool {
+        matches!(self.pixel_format(), gl::DEPTH_COMPONENT | gl::DEPTH_STENCIL)
+    }
+
+    /// Returns if the format stores stencil values.
+    pub fn has_stencil(self) -> bool {
+        self.pixel_format() == gl::DEPTH_STENCIL
+    }
+
+    /// Returns if the format stores unnormalized integers, which have to be sampled with `usampler2D` in shaders.
+    pub fn is_integer(self) -> bool {
+        self == TextureFormat::R32Ui
+    }
+
+    /// Returns if the format stores floating point values.
+    pub fn is_float(self) -> bool {
+        matches!(
+            self,
+            TextureFormat::R16F
+                | TextureFormat::Rg16F
+                | TextureFormat::Rgba16F
+                | TextureFormat::R32F
+                | TextureFormat::Rg32F
+                | TextureFormat::Rgba32F
+                | TextureFormat::R11G11B10F
+        ) || matches!(self, TextureFormat::Compressed(format) if format.is_float())
+    }
+
+    /// Returns if the format stores sRGB encoded colors.
+    pub fn is_srgb(self) -> bool {
+        matches!(self, TextureFormat::Srgb8 | TextureFormat::Srgb8Alpha8)
+            || matches!(self, TextureFormat::Compressed(format) if format.is_srgb())
+    }
+
+    /// Returns if the format is block compressed.
+    pub fn is_compressed(self) -> bool {
+        matches!(self, TextureFormat::Compressed(_))
+    }
+
+    /// Internal function returning the filter a new texture of this format starts with.
+    /// Integer and depth textures can not be filtered linearly.
+    pub(crate) fn default_filter(self) -> GLenum {
+        if self.is_integer() || self.is_depth() {
+            gl::NEAREST
+        } else {
+            gl::LINEAR
+        }
+    }
+
+    /// Internal function returning the format an image is stored in by texture types which can not swizzle
+    /// single channel images into grayscale, such as cubemaps and texture arrays. These images are expanded instead.
+    pub(crate) fn from_image_expanded(img: &DynamicImage, config: &TextureConfig) -> Self {
+        let format = match config.format {
+            Some(format) => format,
+            None => match TextureFormat::from_image(img) {
+                format if format.channel_count() <= 2 => TextureFormat::Rgba8,
+                format => format,
+            },
+        };
+
+        if config.srgb {
+            format.to_srgb()
+        } else {
+            format
+        }
+    }
+
+    /// Internal function to convert an image into the pixel data of this format.
+    pub(crate) fn image_data(self, img: &DynamicImage) -> Result<Vec<u8>, String> {
+        let data = match self {
+            TextureFormat::R8 => img.to_luma8().into_raw(),
+            TextureFormat::Rg8 => img.to_luma_alpha8().into_raw(),
+            TextureFormat::Rgb8 | TextureFormat::Srgb8 => img.to_rgb8().into_raw(),
+            TextureFormat::Rgba8 | TextureFormat::Srgb8Alpha8 => img.to_rgba8().into_raw(),
+            TextureFormat::R16 => to_bytes(img.to_luma16().as_raw(), u16::to_ne_bytes),
+            TextureFormat::Rgba16 => to_bytes(img.to_rgba16().as_raw(), u16::to_ne_bytes),
+            TextureFormat::R16F | TextureFormat::R32F => to_bytes(img.to_luma32f().as_raw(), f32::to_ne_bytes),
+            TextureFormat::Rg16F | TextureFormat::Rg32F => {
+                to_bytes(img.to_luma_alpha32f().as_raw(), f32::to_ne_bytes)
+            }
+            TextureFormat::R11G11B10F => to_bytes(img.to_rgb32f().as_raw(), f32::to_ne_bytes),
+            TextureFormat::Rgba16F | TextureFormat::Rgba32F => to_bytes(img.to_rgba32f().as_raw(), f32::to_ne_bytes),
+            TextureFormat::R32Ui => {
+                let values = img.to_luma16().pixels().map(|pixel| pixel.0[0] as u32).collect::<Vec<_>>();
+                to_bytes(&values, u32::to_ne_bytes)
+            }
+            TextureFormat::Depth16
+            | TextureFormat::Depth24
+            | TextureFormat::Depth32F
+            | TextureFormat::Depth24Stencil8
+            | TextureFormat::Depth32FStencil8
+            | TextureFormat::Compressed(_) => {
+                return Err(format!("[FerrousGl Error] Can not create a {:?} texture from an image", self));
+            }
+        };
+
+        Ok(data)
+    }
+}
+
+/// Internal function returning the minification filter [`Texture::set_mipmap_and_filtering`] has always used, which
+/// swaps the meaning of the mipmap type and base filter compared to [`min_filter`] when mipmaps are used.
+fn legacy_min_filter(mipmap: MipmapType, filter: FilterMode) -> GLenum {
+    match (mipmap, filter) {
+        (MipmapType::Nearest, FilterMode::Linear) => gl::NEAREST_MIPMAP_LINEAR,
+        (MipmapType::Linear, FilterMode::Nearest) => gl::LINEAR_MIPMAP_NEAREST,
+        _ => min_filter(mipmap, filter),
+    }
+}
+
+/// Internal function to encode a linear color channel with the sRGB transfer function.
+pub(crate) fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Internal function to convert a slice of numbers into their bytes.
+fn to_bytes<T: Copy, const N: usize>(values: &[T], convert: fn(T) -> [u8; N]) -> Vec<u8> {
+    values.iter().flat_map(|&value| convert(value)).collect()
+}
+
+/// Internal function to turn RGBA pixels read back from a single or dual channel texture into `(l, l, l, a)`, where
+/// the luminance is stored in red and the alpha, if there is one, in green.
+fn expand_luminance<T: Copy>(pixels: &mut [T], channel_count: usize) {
+    for pixel in pixels.chunks_exact_mut(4) {
+        let alpha = if channel_count == 2 { pixel[1] } else { pixel[3] };
+        pixel.copy_from_slice(&[pixel[0], pixel[0], pixel[0], alpha]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayAlphaImage, GrayImage, LumaA};
+
+    /// Simulates reading a texture back as RGBA, which fills missing color channels with 0 and alpha with the maximum.
+    fn read_back_as_rgba(data: &[u8], channel_count: usize) -> Vec<u8> {
+        data.chunks_exact(channel_count)
+            .flat_map(|pixel| {
+                let mut rgba = [0, 0, 0, 255];
+                rgba[..channel_count].copy_from_slice(pixel);
+                rgba
+            })
+            .collect()
+    }
+
+    #[test]
+    fn grayscale_round_trip() {
+        let image = DynamicImage::ImageLuma8(GrayImage::from_fn(4, 3, |x, y| image::Luma([(x * 60 + y * 7) as u8])));
+        let format = TextureFormat::from_image(&image);
+        assert_eq!(format, TextureFormat::R8);
+
+        let mut pixels = read_back_as_rgba(&format.image_data(&image).unwrap(), 1);
+        expand_luminance(&mut pixels, format.channel_count());
+        assert_eq!(pixels, image.to_rgba8().into_raw());
+    }
+
+    #[test]
+    fn grayscale_alpha_round_trip() {
+        let image = DynamicImage::ImageLumaA8(GrayAlphaImage::from_fn(3, 2, |x, y| LumaA([(x * 80) as u8, (y * 100 + 20) as u8])));
+        let format = TextureFormat::from_image(&image);
+        assert_eq!(format, TextureFormat::Rg8);
+
+        let mut pixels = read_back_as_rgba(&format.image_data(&image).unwrap(), 2);
+        expand_luminance(&mut pixels, format.channel_count());
+        assert_eq!(pixels, image.to_rgba8().into_raw());
+    }
+}
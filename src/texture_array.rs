@@ -0,0 +1,414 @@
+extern crate gl;
+extern crate image;
+
+use gl::types::*;
+use image::DynamicImage;
+use std::ptr;
+
+use crate::sampler::{ParameterTarget, SamplerSettings};
+use crate::{TextureConfig, TextureFormat};
+
+/// Represents an OpenGL 2D texture array, a stack of equally sized 2D textures (layers) which are bound as a single
+/// texture. Useful for tile sets or terrain textures, sampled with `sampler2DArray` and `vec3(uv, layer)` in shaders.
+pub struct TextureArray {
+    pub(crate) id: GLuint,
+    width: u32,
+    height: u32,
+    layers: u32,
+    pub(crate) format: TextureFormat,
+}
+
+impl TextureArray {
+    /// Creates an empty texture array with the specified size, amount of layers and texture format.
+    pub fn new_empty(width: u32, height: u32, layers: u32, format: TextureFormat) -> Result<Self, String> {
+        if format.is_compressed() {
+            return Err(format!("[FerrousGl Error] Can not create an empty {:?} texture array", format));
+        }
+
+        let mut texture_id = 0;
+
+        unsafe {
+            gl::GenTextures(1, &mut texture_id);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, texture_id);
+
+            gl::TexImage3D(
+                gl::TEXTURE_2D_ARRAY,
+                0,
+                format.internal_format() as i32,
+                width as i32,
+                height as i32,
+                layers as i32,
+                0,
+                format.pixel_format(),
+                format.pixel_type(),
+                ptr::null(),
+            );
+
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_MIN_FILTER, format.default_filter() as i32);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_MAG_FILTER, format.default_filter() as i32);
+
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, 0);
+        }
+
+        Ok(TextureArray {
+            id: texture_id,
+            width,
+            height,
+            layers,
+            format,
+        })
+    }
+
+    /// Creates a new texture array with one layer per image. All images have to be of the same size.
+    pub fn from_images(images: &[DynamicImage]) -> Result<Self, String> {
+        Self::from_images_with_config(images, &TextureConfig::default())
+    }
+
+    /// Creates a new texture array with one layer per image using the given texture config.
+    /// All images have to be of the same size.
+    pub fn from_images_with_config(images: &[DynamicImage], config: &TextureConfig) -> Result<Self, String> {
+        let first = images
+            .first()
+            .ok_or_else(|| "[FerrousGl Error] A texture array needs at least one image".to_string())?;
+
+        let format = TextureFormat::from_image_expanded(first, config);
+        let array = Self::new_empty(first.width(), first.height(), images.len() as u32, format)?;
+
+        for (layer, img) in images.iter().enumerate() {
+            array.update_layer(layer as u32, img)?;
+        }
+
+        array.set_sampler_settings(&config.sampler);
+        if config.mipmaps {
+            array.generate_mipmaps();
+        }
+
+        Ok(array)
+    }
+
+    /// Replaces the pixels of a layer with an image, which is converted into the format of the texture array.
+    pub fn update_layer(&self, layer: u32, img: &DynamicImage) -> Result<(), String> {
+        if img.width() != self.width || img.height() != self.height {
+            return Err(format!(
+                "[FerrousGl Error] Expected a {}x{} image for the texture array layer, but got {}x{}",
+                self.width,
+                self.height,
+                img.width(),
+                img.height()
+            ));
+        }
+
+        let data = self.format.image_data(img)?;
+        self.update_layer_data(layer, &data)
+    }
+
+    /// Replaces the pixels of a layer. The data has the same layout as for [`crate::Texture::update_region`].
+    pub fn update_layer_data(&self, layer: u32, data: &[u8]) -> Result<(), String> {
+        if layer >= self.layers {
+            return Err(format!(
+                "[FerrousGl Error] Layer {} is outside of the texture array with {} layers",
+                layer, self.layers
+            ));
+        }
+
+        let expected_length = self.width as usize * self.height as usize * self.format.bytes_per_pixel();
+        if data.len() != expected_length {
+            return Err(format!(
+                "[FerrousGl Error] Expected {} bytes of {:?} pixel data, but got {} bytes",
+                expected_length,
+                self.format,
+                data.len()
+            ));
+        }
+
+        self.with_binding(|| unsafe {
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TexSubImage3D(
+                gl::TEXTURE_2D_ARRAY,
+                0,
+                0,
+                0,
+                layer as i32,
+                self.width as i32,
+                self.height as i32,
+                1,
+                self.format.pixel_format(),
+                self.format.pixel_type(),
+                data.as_ptr() as *const GLvoid,
+            );
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+        });
+
+        Ok(())
+    }
+
+    /// Binds the texture array to a specific texture unit which can be used to set a uniform texture.
+    pub fn bind(&self, texture_unit: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + texture_unit);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, self.id);
+        }
+    }
+
+    /// Unbinds the texture array.
+    pub fn unbind(&self) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, 0);
+        }
+    }
+
+    /// Sets how the texture array is sampled in shaders. The texture array does not have to be bound for this.
+    pub fn set_sampler_settings(&self, settings: &SamplerSettings) {
+        self.with_binding(|| settings.apply(ParameterTarget::Texture(gl::TEXTURE_2D_ARRAY)));
+    }
+
+    /// Generates all mipmap levels of every layer, for example after updating or rendering into layers.
+    pub fn generate_mipmaps(&self) {
+        if self.format.is_integer() || self.format.is_depth() {
+            return;
+        }
+
+        self.with_binding(|| unsafe {
+            gl::GenerateMipmap(gl::TEXTURE_2D_ARRAY);
+        });
+    }
+
+    /// Returns the width of every layer.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height of every layer.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the amount of layers.
+    pub fn layers(&self) -> u32 {
+        self.layers
+    }
+
+    /// Returns the format the texture array is stored in on the GPU.
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    /// Internal function to run OpenGL calls with the texture array bound, restoring the previous binding afterwards.
+    fn with_binding<R>(&self, f: impl FnOnce() -> R) -> R {
+        unsafe {
+            let mut previous_texture = 0;
+            gl::GetIntegerv(gl::TEXTURE_BINDING_2D_ARRAY, &mut previous_texture);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, self.id);
+
+            let result = f();
+
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, previous_texture as GLuint);
+            result
+        }
+    }
+}
+
+impl Drop for TextureArray {
+    /// Cleans up the texture array when it goes out of scope.
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.id);
+        }
+    }
+}
+
+/// Represents an OpenGL 3D texture, a volume of pixels (voxels). Useful for volume rendering, 3D noise or color grading
+/// lookup tables, sampled with `sampler3D` and `vec3` coordinates in shaders.
+pub struct Texture3D {
+    pub(crate) id: GLuint,
+    width: u32,
+    height: u32,
+    depth: u32,
+    pub(crate) format: TextureFormat,
+}
+
+impl Texture3D {
+    /// Creates an empty 3D texture with the specified size and texture format.
+    pub fn new_empty(width: u32, height: u32, depth: u32, format: TextureFormat) -> Result<Self, String> {
+        Self::create(width, height, depth, format, ptr::null())
+    }
+
+    /// Creates a new 3D texture from volume data, laid out slice after slice from front (`z = 0`) to back.
+    /// Every slice has the same layout as for [`crate::Texture::update_region`].
+    pub fn from_data(width: u32, height: u32, depth: u32, format: TextureFormat, data: &[u8]) -> Result<Self, String> {
+        check_volume_length(width, height, depth, format, data.len())?;
+        Self::create(width, height, depth, format, data.as_ptr() as *const GLvoid)
+    }
+
+    /// Internal function to create the 3D texture storage, optionally filled with data.
+    fn create(width: u32, height: u32, depth: u32, format: TextureFormat, data: *const GLvoid) -> Result<Self, String> {
+        if format.is_depth() || format.is_compressed() {
+            return Err(format!("[FerrousGl Error] Can not create a 3D texture with the {:?} format", format));
+        }
+
+        let mut texture_id = 0;
+
+        unsafe {
+            gl::GenTextures(1, &mut texture_id);
+            gl::BindTexture(gl::TEXTURE_3D, texture_id);
+
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TexImage3D(
+                gl::TEXTURE_3D,
+                0,
+                format.internal_format() as i32,
+                width as i32,
+                height as i32,
+                depth as i32,
+                0,
+                format.pixel_format(),
+                format.pixel_type(),
+                data,
+            );
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+
+            gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_WRAP_R, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_MIN_FILTER, format.default_filter() as i32);
+            gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_MAG_FILTER, format.default_filter() as i32);
+
+            gl::BindTexture(gl::TEXTURE_3D, 0);
+        }
+
+        Ok(Texture3D {
+            id: texture_id,
+            width,
+            height,
+            depth,
+            format,
+        })
+    }
+
+    /// Writes volume data into a region of the 3D texture, starting at the corner `(x, y, z)`.
+    /// The data is laid out the same way as for [`Texture3D::from_data`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_region(&self, x: u32, y: u32, z: u32, width: u32, height: u32, depth: u32, data: &[u8]) -> Result<(), String> {
+        let inside = |start: u32, size: u32, limit: u32| start.checked_add(size).is_some_and(|end| end <= limit);
+        if !inside(x, width, self.width) || !inside(y, height, self.height) || !inside(z, depth, self.depth) {
+            return Err(format!(
+                "[FerrousGl Error] Region {}x{}x{} at ({}, {}, {}) is outside of the {}x{}x{} texture",
+                width, height, depth, x, y, z, self.width, self.height, self.depth
+            ));
+        }
+        check_volume_length(width, height, depth, self.format, data.len())?;
+
+        self.with_binding(|| unsafe {
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TexSubImage3D(
+                gl::TEXTURE_3D,
+                0,
+                x as i32,
+                y as i32,
+                z as i32,
+                width as i32,
+                height as i32,
+                depth as i32,
+                self.format.pixel_format(),
+                self.format.pixel_type(),
+                data.as_ptr() as *const GLvoid,
+            );
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+        });
+
+        Ok(())
+    }
+
+    /// Binds the 3D texture to a specific texture unit which can be used to set a uniform texture.
+    pub fn bind(&self, texture_unit: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + texture_unit);
+            gl::BindTexture(gl::TEXTURE_3D, self.id);
+        }
+    }
+
+    /// Unbinds the 3D texture.
+    pub fn unbind(&self) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_3D, 0);
+        }
+    }
+
+    /// Sets how the 3D texture is sampled in shaders. The 3D texture does not have to be bound for this.
+    pub fn set_sampler_settings(&self, settings: &SamplerSettings) {
+        self.with_binding(|| settings.apply(ParameterTarget::Texture(gl::TEXTURE_3D)));
+    }
+
+    /// Generates all mipmap levels of the 3D texture.
+    pub fn generate_mipmaps(&self) {
+        if self.format.is_integer() {
+            return;
+        }
+
+        self.with_binding(|| unsafe {
+            gl::GenerateMipmap(gl::TEXTURE_3D);
+        });
+    }
+
+    /// Returns the width of the 3D texture.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height of the 3D texture.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the depth (amount of slices) of the 3D texture.
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    /// Returns the format the 3D texture is stored in on the GPU.
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    /// Internal function to run OpenGL calls with the 3D texture bound, restoring the previous binding afterwards.
+    fn with_binding<R>(&self, f: impl FnOnce() -> R) -> R {
+        unsafe {
+            let mut previous_texture = 0;
+            gl::GetIntegerv(gl::TEXTURE_BINDING_3D, &mut previous_texture);
+            gl::BindTexture(gl::TEXTURE_3D, self.id);
+
+            let result = f();
+
+            gl::BindTexture(gl::TEXTURE_3D, previous_texture as GLuint);
+            result
+        }
+    }
+}
+
+impl Drop for Texture3D {
+    /// Cleans up the 3D texture when it goes out of scope.
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.id);
+        }
+    }
+}
+
+/// Internal function to check if volume data has the right size for a region.
+fn check_volume_length(width: u32, height: u32, depth: u32, format: TextureFormat, length: usize) -> Result<(), String> {
+    let expected_length = (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|pixels| pixels.checked_mul(depth as usize))
+        .and_then(|pixels| pixels.checked_mul(format.bytes_per_pixel()))
+        .ok_or_else(|| format!("[FerrousGl Error] A {}x{}x{} volume is too large", width, height, depth))?;
+    if length != expected_length {
+        return Err(format!(
+            "[FerrousGl Error] Expected {} bytes of {:?} volume data, but got {} bytes",
+            expected_length, format, length
+        ));
+    }
+
+    Ok(())
+}
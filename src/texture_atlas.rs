@@ -0,0 +1,486 @@
+extern crate glam;
+extern crate image;
+
+use glam::Vec2;
+use image::DynamicImage;
+use std::collections::HashMap;
+
+use crate::{SamplerSettings, Texture, TextureConfig, TextureFormat, WrapMode};
+
+/// Struct to more easily allow setting atlas features when creating a texture atlas.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtlasConfig {
+    /// The width of each atlas page in pixels.
+    pub page_width: u32,
+    /// The height of each atlas page in pixels.
+    pub page_height: u32,
+    /// The amount of empty pixels between images.
+    pub padding: u32,
+    /// The amount of pixels the border of each image is repeated outwards, which prevents neighbouring
+    /// images from bleeding into each other when sampling with linear filtering or mipmaps.
+    pub extrude: u32,
+    /// Whether images may be rotated by 90 degrees to pack them more tightly, see [`AtlasRegion::uv_corners`].
+    pub allow_rotation: bool,
+    /// The config of the page textures. `None` as format stores the pages as [`TextureFormat::Rgba8`].
+    pub texture: TextureConfig,
+}
+
+impl Default for AtlasConfig {
+    fn default() -> Self {
+        Self {
+            page_width: 1024,
+            page_height: 1024,
+            padding: 2,
+            extrude: 1,
+            allow_rotation: false,
+            texture: TextureConfig {
+                sampler: SamplerSettings::with_wrap(WrapMode::ClampToEdge),
+                mipmaps: false,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// The place of a single image inside of a texture atlas.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasRegion {
+    /// The index of the page the image was packed into.
+    pub page: usize,
+    /// The position and size of the image on the page in pixels. If the image is rotated, width and height are swapped.
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Whether the image was rotated by 90 degrees clockwise when packing it.
+    pub rotated: bool,
+    /// The texture coordinates of the first pixel (the top left pixel of the image file) on the page.
+    pub uv_min: Vec2,
+    /// The texture coordinates of the opposite corner of the region on the page.
+    pub uv_max: Vec2,
+}
+
+impl AtlasRegion {
+    /// Returns the texture coordinates of the corners of the original image in the order top left, top right,
+    /// bottom right, bottom left, where top is the first row of the image file. This takes rotation into account.
+    pub fn uv_corners(&self) -> [Vec2; 4] {
+        let (min, max) = (self.uv_min, self.uv_max);
+
+        if self.rotated {
+            [
+                Vec2::new(max.x, min.y),
+                max,
+                Vec2::new(min.x, max.y),
+                min,
+            ]
+        } else {
+            [
+                min,
+                Vec2::new(max.x, min.y),
+                max,
+                Vec2::new(min.x, max.y),
+            ]
+        }
+    }
+}
+
+/// Builder collecting images which are packed into a [`TextureAtlas`] all at once. Packing everything at once
+/// gives tighter pages than adding the images to an atlas one by one, as the images can be sorted by size first.
+pub struct TextureAtlasBuilder {
+    config: AtlasConfig,
+    images: Vec<(String, DynamicImage)>,
+}
+
+impl TextureAtlasBuilder {
+    /// Creates a new builder using the given atlas config.
+    pub fn new(config: AtlasConfig) -> Self {
+        TextureAtlasBuilder {
+            config,
+            images: Vec::new(),
+        }
+    }
+
+    /// Adds an image under a name which is used to look up its region later.
+    pub fn add_image(mut self, name: &str, img: DynamicImage) -> Self {
+        self.images.push((name.to_string(), img));
+        self
+    }
+
+    /// Packs all images into as many pages as needed and uploads them.
+    pub fn build(mut self) -> Result<TextureAtlas, String> {
+        // Placing the tallest images first keeps the skyline flat
+        self.images
+            .sort_by_key(|(_, img)| std::cmp::Reverse((img.height().max(img.width()), img.height())));
+
+        let mut atlas = TextureAtlas::new(self.config);
+        atlas.generate_mipmaps = false;
+
+        for (name, img) in &self.images {
+            atlas.add_image(name, img)?;
+        }
+
+        atlas.generate_mipmaps = true;
+        for page in &atlas.pages {
+            atlas.update_mipmaps(&page.texture)?;
+        }
+
+        Ok(atlas)
+    }
+}
+
+/// Represents a texture atlas, many images packed into one or more large textures (pages) so they can be drawn
+/// without switching textures. Images are looked up by name and can still be added after creation.
+pub struct TextureAtlas {
+    config: AtlasConfig,
+    pages: Vec<AtlasPage>,
+    regions: HashMap<String, AtlasRegion>,
+    generate_mipmaps: bool,
+}
+
+/// A single page of a texture atlas with the skyline describing its free space.
+struct AtlasPage {
+    texture: Texture,
+    packer: SkylinePacker,
+}
+
+impl TextureAtlas {
+    /// Creates a new, empty texture atlas. Pages are created as images are added.
+    pub fn new(config: AtlasConfig) -> Self {
+        TextureAtlas {
+            config,
+            pages: Vec::new(),
+            regions: HashMap::new(),
+            generate_mipmaps: true,
+        }
+    }
+
+    /// Packs an image into the atlas and uploads it, creating a new page if it does not fit on any existing page.
+    pub fn add_image(&mut self, name: &str, img: &DynamicImage) -> Result<AtlasRegion, String> {
+        if self.regions.contains_key(name) {
+            return Err(format!("[FerrousGl Error] The texture atlas already contains an image named '{}'", name));
+        }
+        if img.width() == 0 || img.height() == 0 {
+            return Err(format!("[FerrousGl Error] Can not add the empty image '{}' to a texture atlas", name));
+        }
+
+        // Saturating keeps huge borders from wrapping around, they are rejected by the size check below
+        let border = self.config.extrude.saturating_mul(2).saturating_add(self.config.padding);
+        let (width, height) = (img.width().saturating_add(border), img.height().saturating_add(border));
+        let allow_rotation = self.config.allow_rotation;
+
+        let (page_width, page_height) = (self.config.page_width, self.config.page_height);
+        let fits = |width, height| width <= page_width && height <= page_height;
+        if !(fits(width, height) || allow_rotation && fits(height, width)) {
+            return Err(format!(
+                "[FerrousGl Error] The {}x{} image '{}' does not fit on a {}x{} atlas page",
+                img.width(),
+                img.height(),
+                name,
+                page_width,
+                page_height
+            ));
+        }
+
+        let placement = self
+            .pages
+            .iter_mut()
+            .enumerate()
+            .find_map(|(index, page)| page.packer.insert(width, height, allow_rotation).map(|place| (index, place)));
+
+        let (page, (x, y, rotated)) = match placement {
+            Some(placement) => placement,
+            None => {
+                let mut page = self.create_page()?;
+                let place = page
+                    .packer
+                    .insert(width, height, allow_rotation)
+                    .ok_or_else(|| "[FerrousGl Error] Could not pack an image onto an empty atlas page".to_string())?;
+                self.pages.push(page);
+                (self.pages.len() - 1, place)
+            }
+        };
+
+        let img = if rotated { img.rotate90() } else { img.clone() };
+        let texture = &self.pages[page].texture;
+        let format = texture.format();
+
+        let extrude = self.config.extrude;
+        let data = extrude_pixels(&format.image_data(&img)?, img.width(), img.height(), extrude, format);
+        texture.update_region(x, y, img.width() + extrude * 2, img.height() + extrude * 2, &data)?;
+        self.update_mipmaps(texture)?;
+
+        let (x, y) = (x + extrude, y + extrude);
+        let page_size = Vec2::new(self.config.page_width as f32, self.config.page_height as f32);
+        let region = AtlasRegion {
+            page,
+            x,
+            y,
+            width: img.width(),
+            height: img.height(),
+            rotated,
+            uv_min: Vec2::new(x as f32, y as f32) / page_size,
+            uv_max: Vec2::new((x + img.width()) as f32, (y + img.height()) as f32) / page_size,
+        };
+
+        self.regions.insert(name.to_string(), region);
+        Ok(region)
+    }
+
+    /// Returns the region of the image with the given name.
+    pub fn region(&self, name: &str) -> Option<&AtlasRegion> {
+        self.regions.get(name)
+    }
+
+    /// Returns an iterator over the names and regions of all images in the atlas.
+    pub fn regions(&self) -> impl Iterator<Item = (&str, &AtlasRegion)> {
+        self.regions.iter().map(|(name, region)| (name.as_str(), region))
+    }
+
+    /// Returns the texture of the page with the given index.
+    pub fn page(&self, index: usize) -> Option<&Texture> {
+        self.pages.get(index).map(|page| &page.texture)
+    }
+
+    /// Returns the amount of pages in the atlas.
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Internal function to create and clear a new page texture.
+    fn create_page(&self) -> Result<AtlasPage, String> {
+        let (width, height) = (self.config.page_width, self.config.page_height);
+
+        let mut texture_config = self.config.texture.clone();
+        texture_config.format = Some(texture_config.format.unwrap_or(TextureFormat::Rgba8));
+        let texture = Texture::new_empty_with_config(width, height, &texture_config)?;
+
+        let length = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|pixels| pixels.checked_mul(texture.format().bytes_per_pixel()))
+            .ok_or_else(|| format!("[FerrousGl Error] A {}x{} atlas page is too large", width, height))?;
+        let empty = vec![0; length];
+        texture.update_region(0, 0, width, height, &empty)?;
+
+        Ok(AtlasPage {
+            texture,
+            packer: SkylinePacker::new(width, height),
+        })
+    }
+
+    /// Internal function to regenerate the mipmaps of a page after it changed, if enabled.
+    fn update_mipmaps(&self, texture: &Texture) -> Result<(), String> {
+        if self.generate_mipmaps && self.config.texture.mipmaps {
+            texture.generate_mipmaps()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Internal packer placing rectangles on the lowest possible position of a skyline, the outline of the
+/// rectangles placed so far.
+struct SkylinePacker {
+    width: u32,
+    height: u32,
+    skyline: Vec<SkylineNode>,
+}
+
+/// A horizontal segment of the skyline.
+#[derive(Clone, Copy)]
+struct SkylineNode {
+    x: u32,
+    y: u32,
+    width: u32,
+}
+
+impl SkylinePacker {
+    fn new(width: u32, height: u32) -> Self {
+        SkylinePacker {
+            width,
+            height,
+            skyline: vec![SkylineNode { x: 0, y: 0, width }],
+        }
+    }
+
+    /// Finds a place for a rectangle and marks it as used. Returns the position and whether the rectangle was rotated.
+    fn insert(&mut self, width: u32, height: u32, allow_rotation: bool) -> Option<(u32, u32, bool)> {
+        let mut best = self.find_position(width, height).map(|(index, y)| (index, y, y + height, false));
+
+        if allow_rotation && width != height {
+            if let Some((index, y)) = self.find_position(height, width) {
+                let lower = match best {
+                    Some((_, _, top, _)) => y + width < top,
+                    None => true,
+                };
+                if lower {
+                    best = Some((index, y, y + width, true));
+                }
+            }
+        }
+
+        let (index, y, _, rotated) = best?;
+        let (width, height) = if rotated { (height, width) } else { (width, height) };
+        let x = self.skyline[index].x;
+        self.add_level(index, x, y + height, width);
+
+        Some((x, y, rotated))
+    }
+
+    /// Returns the skyline node and height with the lowest top edge where the rectangle fits.
+    fn find_position(&self, width: u32, height: u32) -> Option<(usize, u32)> {
+        let mut best: Option<(usize, u32)> = None;
+
+        for index in 0..self.skyline.len() {
+            if let Some(y) = self.fit(index, width, height) {
+                let lower = match best {
+                    Some((_, best_y)) => y < best_y,
+                    None => true,
+                };
+                if lower {
+                    best = Some((index, y));
+                }
+            }
+        }
+
+        best
+    }
+
+    /// Returns the height a rectangle would be placed at when its left edge starts at the given node.
+    fn fit(&self, index: usize, width: u32, height: u32) -> Option<u32> {
+        let x = self.skyline[index].x;
+        if x + width > self.width {
+            return None;
+        }
+
+        let mut y = 0;
+        let mut covered = 0;
+        for node in &self.skyline[index..] {
+            if covered >= width {
+                break;
+            }
+
+            y = y.max(node.y);
+            if y + height > self.height {
+                return None;
+            }
+            covered += node.width;
+        }
+
+        Some(y)
+    }
+
+    /// Inserts a new skyline segment and shortens or removes the segments it covers.
+    fn add_level(&mut self, index: usize, x: u32, y: u32, width: u32) {
+        self.skyline.insert(index, SkylineNode { x, y, width });
+
+        let right = x + width;
+        while index + 1 < self.skyline.len() {
+            let next = &mut self.skyline[index + 1];
+            if next.x >= right {
+                break;
+            }
+
+            let overlap = right - next.x;
+            if next.width <= overlap {
+                self.skyline.remove(index + 1);
+            } else {
+                next.x += overlap;
+                next.width -= overlap;
+                break;
+            }
+        }
+
+        // Merge neighbouring segments of the same height
+        let mut i = 0;
+        while i + 1 < self.skyline.len() {
+            if self.skyline[i].y == self.skyline[i + 1].y {
+                self.skyline[i].width += self.skyline[i + 1].width;
+                self.skyline.remove(i + 1);
+            } else {
+                i += 1;
+            }
+        }
+    }
+}
+
+/// Internal function to surround pixel data with copies of its border pixels.
+fn extrude_pixels(data: &[u8], width: u32, height: u32, extrude: u32, format: TextureFormat) -> Vec<u8> {
+    if extrude == 0 {
+        return data.to_vec();
+    }
+
+    let bytes_per_pixel = format.bytes_per_pixel();
+    let (extruded_width, extruded_height) = (width + extrude * 2, height + extrude * 2);
+    let mut extruded = Vec::with_capacity((extruded_width * extruded_height) as usize * bytes_per_pixel);
+
+    for y in 0..extruded_height {
+        let source_y = y.saturating_sub(extrude).min(height - 1);
+        for x in 0..extruded_width {
+            let source_x = x.saturating_sub(extrude).min(width - 1);
+            let start = (source_y * width + source_x) as usize * bytes_per_pixel;
+            extruded.extend_from_slice(&data[start..start + bytes_per_pixel]);
+        }
+    }
+
+    extruded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns if two placed rectangles `(x, y, width, height)` overlap.
+    fn overlaps(a: (u32, u32, u32, u32), b: (u32, u32, u32, u32)) -> bool {
+        a.0 < b.0 + b.2 && b.0 < a.0 + a.2 && a.1 < b.1 + b.3 && b.1 < a.1 + a.3
+    }
+
+    /// Inserts the rectangles and returns where they were placed, with their size after rotation.
+    fn pack(packer: &mut SkylinePacker, sizes: &[(u32, u32)], allow_rotation: bool) -> Vec<(u32, u32, u32, u32)> {
+        sizes
+            .iter()
+            .map(|&(width, height)| {
+                let (x, y, rotated) = packer.insert(width, height, allow_rotation).unwrap();
+                if rotated {
+                    (x, y, height, width)
+                } else {
+                    (x, y, width, height)
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn packs_without_overlap() {
+        let mut packer = SkylinePacker::new(64, 64);
+        let sizes = [(10, 20), (30, 5), (16, 16), (8, 30), (25, 12), (5, 5), (40, 8), (12, 12), (20, 20)];
+        let placed = pack(&mut packer, &sizes, false);
+
+        for (index, &a) in placed.iter().enumerate() {
+            assert!(a.0 + a.2 <= 64 && a.1 + a.3 <= 64);
+            for &b in &placed[index + 1..] {
+                assert!(!overlaps(a, b), "{:?} overlaps {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn rotates_rectangles_that_only_fit_rotated() {
+        let mut packer = SkylinePacker::new(32, 8);
+        assert_eq!(packer.insert(4, 24, false), None);
+
+        let (x, y, rotated) = packer.insert(4, 24, true).unwrap();
+        assert!(rotated);
+        assert_eq!((x, y), (0, 0));
+    }
+
+    #[test]
+    fn rejects_rectangles_once_full() {
+        let mut packer = SkylinePacker::new(16, 16);
+        for _ in 0..4 {
+            assert!(packer.insert(8, 8, false).is_some());
+        }
+
+        assert_eq!(packer.insert(1, 1, true), None);
+        assert_eq!(SkylinePacker::new(16, 16).insert(17, 1, false), None);
+    }
+}
@@ -0,0 +1,168 @@
+extern crate gl;
+
+use crate::fullscreen::{FullscreenTriangle, FULLSCREEN_VERTEX_SHADER};
+use crate::{Shader, Texture};
+
+/// Enum representing the curves used to map HDR colors into the displayable range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TonemapOperator {
+    /// Clamps colors above 1.0, useful for comparing against the other operators.
+    Linear,
+    /// The Reinhard operator `c / (1 + c)`, which never reaches pure white.
+    Reinhard,
+    /// The Reinhard operator extended with a white point, the brightness that is mapped to pure white.
+    ReinhardExtended { white_point: f32 },
+    /// An approximation of the ACES filmic curve, with more contrast and saturation than Reinhard.
+    AcesFilmic,
+}
+
+impl TonemapOperator {
+    /// Internal function returning the index of the operator in the tonemapping shader.
+    pub(crate) fn shader_index(self) -> i32 {
+        match self {
+            TonemapOperator::Linear => 0,
+            TonemapOperator::Reinhard => 1,
+            TonemapOperator::ReinhardExtended { .. } => 2,
+            TonemapOperator::AcesFilmic => 3,
+        }
+    }
+}
+
+/// A built-in fullscreen pass mapping a HDR texture (for example the texture of a [`crate::RenderTexture`] with a
+/// [`crate::TextureFormat::Rgba16F`] color format) onto the current bound framebuffer, usually the window.
+pub struct Tonemapper {
+    shader: Shader,
+    triangle: FullscreenTriangle,
+    operator: TonemapOperator,
+    exposure: f32,
+}
+
+impl Tonemapper {
+    /// Creates a new tonemapper using the ACES filmic operator and an exposure of 1.0.
+    pub fn new() -> Self {
+        Tonemapper {
+            shader: Shader::new_from_source(FULLSCREEN_VERTEX_SHADER, TONEMAPPING_FRAGMENT_SHADER),
+            triangle: FullscreenTriangle::new(),
+            operator: TonemapOperator::AcesFilmic,
+            exposure: 1.0,
+        }
+    }
+
+    /// Sets the curve used to map HDR colors into the displayable range.
+    pub fn set_operator(&mut self, operator: TonemapOperator) {
+        self.operator = operator;
+    }
+
+    /// Returns the curve used to map HDR colors into the displayable range.
+    pub fn operator(&self) -> TonemapOperator {
+        self.operator
+    }
+
+    /// Sets the exposure, which all colors are multiplied with before tonemapping.
+    /// Use `2.0f32.powf(stops)` to work with exposure stops instead.
+    pub fn set_exposure(&mut self, exposure: f32) {
+        self.exposure = exposure;
+    }
+
+    /// Returns the exposure, which all colors are multiplied with before tonemapping.
+    pub fn exposure(&self) -> f32 {
+        self.exposure
+    }
+
+    /// Tonemaps a HDR texture onto the whole viewport of the current bound framebuffer. The result is encoded to sRGB,
+    /// either by the framebuffer if it is an sRGB framebuffer with sRGB conversion enabled, or by the shader otherwise.
+    /// Depth testing is disabled while drawing.
+    pub fn render(&self, source: &Texture) {
+        unsafe {
+            let depth_test = gl::IsEnabled(gl::DEPTH_TEST) == gl::TRUE;
+            gl::Disable(gl::DEPTH_TEST);
+
+            self.shader.bind_program();
+            source.bind(0);
+            self.shader.set_uniform_texture("hdrTexture", 0);
+            self.shader.set_uniform_1i("operator", self.operator.shader_index());
+            self.shader.set_uniform_1f("exposure", self.exposure);
+            if let TonemapOperator::ReinhardExtended { white_point } = self.operator {
+                self.shader.set_uniform_1f("whitePoint", white_point);
+            }
+            self.shader.set_uniform_1i("encodeSrgb", !framebuffer_encodes_srgb() as i32);
+
+            self.triangle.draw();
+
+            source.unbind();
+            self.shader.unbind_program();
+
+            if depth_test {
+                gl::Enable(gl::DEPTH_TEST);
+            }
+        }
+    }
+}
+
+impl Default for Tonemapper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Internal function returning if the current bound framebuffer converts linear colors to sRGB when writing them.
+pub(crate) fn framebuffer_encodes_srgb() -> bool {
+    unsafe {
+        if gl::IsEnabled(gl::FRAMEBUFFER_SRGB) == gl::FALSE {
+            return false;
+        }
+
+        let mut framebuffer = 0;
+        gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut framebuffer);
+        let attachment = if framebuffer == 0 { gl::BACK_LEFT } else { gl::COLOR_ATTACHMENT0 };
+
+        let mut encoding = 0;
+        gl::GetFramebufferAttachmentParameteriv(
+            gl::DRAW_FRAMEBUFFER,
+            attachment,
+            gl::FRAMEBUFFER_ATTACHMENT_COLOR_ENCODING,
+            &mut encoding,
+        );
+
+        encoding as u32 == gl::SRGB
+    }
+}
+
+pub(crate) const TONEMAPPING_FRAGMENT_SHADER: &str = r#"#version 330 core
+in vec2 TexCoord;
+out vec4 FragColor;
+
+uniform sampler2D hdrTexture;
+uniform int operator;
+uniform float exposure;
+uniform float whitePoint;
+uniform bool encodeSrgb;
+
+vec3 acesFilmic(vec3 color) {
+    return clamp((color * (2.51 * color + 0.03)) / (color * (2.43 * color + 0.59) + 0.14), 0.0, 1.0);
+}
+
+vec3 linearToSrgb(vec3 color) {
+    return mix(color * 12.92, 1.055 * pow(color, vec3(1.0 / 2.4)) - 0.055, step(0.0031308, color));
+}
+
+void main() {
+    vec4 hdr = texture(hdrTexture, TexCoord);
+    vec3 color = hdr.rgb * exposure;
+
+    if (operator == 1) {
+        color = color / (1.0 + color);
+    } else if (operator == 2) {
+        color = color * (1.0 + color / (whitePoint * whitePoint)) / (1.0 + color);
+    } else if (operator == 3) {
+        color = acesFilmic(color);
+    }
+
+    color = clamp(color, 0.0, 1.0);
+    if (encodeSrgb) {
+        color = linearToSrgb(color);
+    }
+
+    FragColor = vec4(color, hdr.a);
+}
+"#;
@@ -9,7 +9,9 @@ use glam::{bool, Vec4};
 use glfw::{fail_on_errors, Context, Key, WindowEvent};
 use std::time::{Duration, Instant};
 
-use crate::{Mesh, Texture};
+use crate::blit::Rect;
+use crate::fullscreen::{FullscreenTriangle, PASSTHROUGH_FRAGMENT_SHADER};
+use crate::{CompareFunction, Mesh, RenderTexture, Shader, Texture, TextureFormat};
 
 /// A struct to manage an OpenGL context, window, rendering and input!
 pub struct GlWindow {
@@ -26,6 +28,8 @@ pub struct GlWindow {
     previous_pressed_keys: HashSet<WindowKey>,
     fb_texture: Texture,       // Persistent framebuffer texture
     depth_texture: Texture,    // Persistent depth texture
+    fullscreen_triangle: FullscreenTriangle,
+    passthrough_shader: Shader,
 }
 
 impl GlWindow {
@@ -40,6 +44,8 @@ impl GlWindow {
         glfw.window_hint(glfw::WindowHint::Resizable(config.resizeable));
         glfw.window_hint(glfw::WindowHint::DoubleBuffer(true));
         glfw.window_hint(glfw::WindowHint::Samples(Some(config.anti_aliasing)));
+        glfw.window_hint(glfw::WindowHint::SRgbCapable(config.srgb_framebuffer));
+        glfw.window_hint(glfw::WindowHint::StencilBits(Some(config.stencil_bits)));
 
         let (mut window, events) = glfw
             .create_window(
@@ -62,7 +68,17 @@ impl GlWindow {
         unsafe {
             gl::Enable(gl::DEPTH_TEST);
             gl::Enable(gl::MULTISAMPLE);
-            gl::Viewport(0, 0, config.width as i32, config.height as i32);
+            // Filter across the edges of cubemap faces, so skyboxes and environment maps show no seams
+            gl::Enable(gl::TEXTURE_CUBE_MAP_SEAMLESS);
+            if config.srgb_framebuffer {
+                gl::Enable(gl::FRAMEBUFFER_SRGB);
+            }
+        }
+
+        // On HiDPI displays the framebuffer has more pixels than the window size in screen coordinates
+        let (framebuffer_width, framebuffer_height) = window.get_framebuffer_size();
+        unsafe {
+            gl::Viewport(0, 0, framebuffer_width, framebuffer_height);
         }
 
         let actual_samples = unsafe {
@@ -75,33 +91,12 @@ impl GlWindow {
             println!("[FerrousGl Error] MSAA Configuration has failed. This is likely a problem with your nvidia driver.\nYou can change the problematic setting by going into NVIDIA Control Panel > Manage 3D Settings and clicking restore.");
         }
 
-        let fb_texture = Texture::new_empty(config.width, config.height)
+        let fb_format = if config.srgb_framebuffer { TextureFormat::Srgb8Alpha8 } else { TextureFormat::Rgba8 };
+        let fb_texture = Texture::new_empty_with_format(framebuffer_width as u32, framebuffer_height as u32, fb_format)
             .expect("Failed to create framebuffer texture");
-        let depth_texture = Texture::new_empty(config.width, config.height)
-            .expect("Failed to create depth texture");
-
-        // Configure depth texture format
-        // In the new() function, modify depth texture creation:
-        unsafe {
-            gl::BindTexture(gl::TEXTURE_2D, depth_texture.id);
-            gl::TexImage2D(
-                gl::TEXTURE_2D,
-                0,
-                gl::DEPTH_COMPONENT32F as i32,  // Use 32-bit float format
-                config.width as i32,
-                config.height as i32,
-                0,
-                gl::DEPTH_COMPONENT,
-                gl::FLOAT,
-                ptr::null(),
-            );
-            // Set texture parameters
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
-            gl::BindTexture(gl::TEXTURE_2D, 0);
-        }
+        let depth_texture =
+            Texture::new_empty_with_format(framebuffer_width as u32, framebuffer_height as u32, TextureFormat::Depth32F)
+                .expect("Failed to create depth texture");
 
         GlWindow {
             glfw,
@@ -117,6 +112,8 @@ impl GlWindow {
             previous_pressed_keys: HashSet::new(),
             fb_texture,
             depth_texture,
+            fullscreen_triangle: FullscreenTriangle::new(),
+            passthrough_shader: Shader::new_fullscreen_from_source(PASSTHROUGH_FRAGMENT_SHADER),
         }
     }
 
@@ -189,6 +186,52 @@ impl GlWindow {
         }
     }
 
+    /// Enables the stencil test. A fragment is only drawn if `func` passes when comparing `reference & mask` with
+    /// `stored_value & mask`, where `stored_value` is the value in the stencil buffer. See [`GlWindow::set_stencil_op`]
+    /// for how the stencil buffer is updated.
+    pub fn set_stencil_test(&self, func: CompareFunction, reference: i32, mask: u32) {
+        unsafe {
+            gl::Enable(gl::STENCIL_TEST);
+            gl::StencilFunc(func.into(), reference, mask);
+        }
+    }
+
+    /// Disables the stencil test, after this fragments are drawn regardless of the stencil buffer and
+    /// the stencil buffer is no longer updated.
+    pub fn disable_stencil_test(&self) {
+        unsafe {
+            gl::Disable(gl::STENCIL_TEST);
+        }
+    }
+
+    /// Sets how the stencil buffer is updated while the stencil test is enabled: when the stencil test fails,
+    /// when the stencil test passes but the depth test fails, and when both pass.
+    pub fn set_stencil_op(&self, stencil_fail: StencilOp, depth_fail: StencilOp, pass: StencilOp) {
+        unsafe {
+            gl::StencilOp(stencil_fail.into(), depth_fail.into(), pass.into());
+        }
+    }
+
+    /// Sets which bits of the stencil buffer can be written, 0 disables writing to the stencil buffer and
+    /// `0xFF` allows writing all bits of an 8-bit stencil buffer. This also affects [`GlWindow::clear_stencil`].
+    pub fn set_stencil_write_mask(&self, mask: u32) {
+        unsafe {
+            gl::StencilMask(mask);
+        }
+    }
+
+    /// Enables or disables the conversion of linear colors written by shaders to sRGB when rendering to sRGB
+    /// framebuffers, such as the window when [`WindowConfig::srgb_framebuffer`] is set or sRGB render textures.
+    pub fn set_srgb_conversion(&self, enabled: bool) {
+        unsafe {
+            if enabled {
+                gl::Enable(gl::FRAMEBUFFER_SRGB);
+            } else {
+                gl::Disable(gl::FRAMEBUFFER_SRGB);
+            }
+        }
+    }
+
     /// Set the preferred Blending Mode.
     pub fn set_blend_mode(&self, blend_mode: BlendMode) {
         unsafe {
@@ -229,13 +272,13 @@ impl GlWindow {
     /// Updates the framebuffer and depth texture. You probably want to do this after rendering anything. 
     /// Clearing the depth buffer or color buffer will not clear these textures.
     pub fn update_framebuffer_textures(&mut self) {
-        let (width, height) = self.get_window_size();
+        let (width, height) = (self.fb_texture.width as i32, self.fb_texture.height as i32);
         
         unsafe {
             // Update color texture
             gl::BindTexture(gl::TEXTURE_2D, self.fb_texture.id);
             gl::CopyTexImage2D(
-                gl::TEXTURE_2D, 0, gl::RGBA,
+                gl::TEXTURE_2D, 0, self.fb_texture.format.internal_format(),
                 0, 0, width, height, 0
             );
             
@@ -250,6 +293,27 @@ impl GlWindow {
         }
     }
 
+    /// Internal function to reallocate the framebuffer and depth texture after the framebuffer was resized.
+    fn resize_framebuffer_textures(&mut self, width: i32, height: i32) {
+        // Minimized windows have a framebuffer size of zero, the old textures are kept until the window is restored
+        if width <= 0 || height <= 0 {
+            return;
+        }
+        if (self.fb_texture.width, self.fb_texture.height) == (width as u32, height as u32) {
+            return;
+        }
+
+        let fb_texture = Texture::new_empty_with_format(width as u32, height as u32, self.fb_texture.format);
+        let depth_texture = Texture::new_empty_with_format(width as u32, height as u32, self.depth_texture.format);
+        match (fb_texture, depth_texture) {
+            (Ok(fb_texture), Ok(depth_texture)) => {
+                self.fb_texture = fb_texture;
+                self.depth_texture = depth_texture;
+            }
+            (Err(e), _) | (_, Err(e)) => println!("{}", e),
+        }
+    }
+
     /// Returns the framebuffer texture.
     pub fn get_framebuffer_texture(&self) -> &Texture {
         &self.fb_texture
@@ -275,6 +339,12 @@ impl GlWindow {
         self.window.get_size()
     }
 
+    /// Returns the size of the windows framebuffer in pixels. On HiDPI displays this is larger than the window size,
+    /// which is measured in screen coordinates.
+    pub fn get_framebuffer_size(&self) -> (i32, i32) {
+        self.window.get_framebuffer_size()
+    }
+
     /// Returns the windows position.
     pub fn get_window_position(&self) -> (i32, i32) {
         self.window.get_pos()
@@ -390,6 +460,7 @@ impl GlWindow {
         self.last_mouse_position = self.get_mouse_position();
 
         self.glfw.poll_events();
+        let mut framebuffer_size = None;
         for (_, event) in glfw::flush_messages(&self.events) {
             match event {
                 WindowEvent::Key(key, _, action, _) => {
@@ -402,6 +473,7 @@ impl GlWindow {
                 }
                 WindowEvent::FramebufferSize(width, height) => {
                     self.update_viewport(width, height);
+                    framebuffer_size = Some((width, height));
                 }
                 WindowEvent::Scroll(xoffset, yoffset) => {
                     self.mouse_wheel_delta = (xoffset, yoffset);
@@ -412,6 +484,9 @@ impl GlWindow {
                 _ => {}
             }
         }
+        if let Some((width, height)) = framebuffer_size {
+            self.resize_framebuffer_textures(width, height);
+        }
         self.window.swap_buffers();
 
         // Calculate frame time and sleep if needed (precise)
@@ -432,14 +507,22 @@ impl GlWindow {
         self.last_frame_time = frame_start;
     }
 
-    /// Updates the OpenGL viewport to match a new window size. This function typically only needs to be used after a
-    /// render texture (or offscreen texture) that has a different size than the window is unbound.
+    /// Updates the OpenGL viewport to match a new window size. Render textures restore the previous viewport when
+    /// they are unbound, so this is usually only needed for rendering into a part of the window.
     pub fn update_viewport(&self, width: i32, height: i32) {
         unsafe {
             gl::Viewport(0, 0, width, height);
         }
     }
 
+    /// Renders into a render texture for the duration of the closure. The render texture is bound with its viewport,
+    /// and afterwards it is resolved if it is multisampled and the previous framebuffer and viewport are restored,
+    /// so calls can be nested.
+    pub fn render_to<R>(&self, render_texture: &RenderTexture, f: impl FnOnce(&GlWindow) -> R) -> R {
+        let _guard = render_texture.bind_scoped();
+        f(self)
+    }
+
     /// Clears the current bound color buffer with the specified color.
     pub fn clear_color(&self, color: Vec4) {
         unsafe {
@@ -455,6 +538,74 @@ impl GlWindow {
         }
     }
 
+    /// Clears the current bound stencil buffer with the specified value.
+    pub fn clear_stencil(&self, value: i32) {
+        unsafe {
+            gl::ClearStencil(value);
+            gl::Clear(gl::STENCIL_BUFFER_BIT);
+        }
+    }
+
+    /// Draws a triangle covering the whole viewport of the current bound framebuffer with the given shader, without
+    /// any vertex data. The shader has to be created with [`Shader::new_fullscreen_from_source`], its fragment shader
+    /// receives the texture coordinate as `in vec2 TexCoord`. Uniforms have to be set before. Depth testing is
+    /// disabled while drawing.
+    pub fn draw_fullscreen(&self, shader: &Shader) {
+        unsafe {
+            let depth_test = gl::IsEnabled(gl::DEPTH_TEST) == gl::TRUE;
+            gl::Disable(gl::DEPTH_TEST);
+
+            shader.bind_program();
+            self.fullscreen_triangle.draw();
+
+            if depth_test {
+                gl::Enable(gl::DEPTH_TEST);
+            }
+        }
+    }
+
+    /// Draws a texture over the whole viewport of the current bound framebuffer, for example the texture of a
+    /// [`RenderTexture`] onto the window. Depth testing is disabled while drawing, blending is applied as set.
+    pub fn draw_texture(&self, texture: &Texture) {
+        self.draw_texture_region(texture, [0.0, 0.0], [1.0, 1.0]);
+    }
+
+    /// Draws a rectangle of a texture into a rectangle of the current bound framebuffer, for example to show a minimap
+    /// or debug view. Both rectangles are measured in pixels from the bottom left corner, `src_rect` in texels of the
+    /// texture and `dst_rect` in pixels of the framebuffer. The viewport is restored afterwards.
+    pub fn draw_texture_rect(&self, texture: &Texture, src_rect: Rect, dst_rect: Rect) {
+        let (width, height) = (texture.width().max(1) as f32, texture.height().max(1) as f32);
+        let uv_offset = [src_rect.x as f32 / width, src_rect.y as f32 / height];
+        let uv_scale = [src_rect.width as f32 / width, src_rect.height as f32 / height];
+
+        let mut viewport = [0; 4];
+        unsafe {
+            gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+            gl::Viewport(dst_rect.x, dst_rect.y, dst_rect.width, dst_rect.height);
+        }
+
+        self.draw_texture_region(texture, uv_offset, uv_scale);
+
+        unsafe {
+            gl::Viewport(viewport[0], viewport[1], viewport[2], viewport[3]);
+        }
+    }
+
+    /// Internal function drawing the region of a texture starting at `uv_offset` with the size `uv_scale` in texture
+    /// coordinates over the whole viewport.
+    fn draw_texture_region(&self, texture: &Texture, uv_offset: [f32; 2], uv_scale: [f32; 2]) {
+        texture.bind(0);
+        self.passthrough_shader.bind_program();
+        self.passthrough_shader.set_uniform_texture("sourceTexture", 0);
+        self.passthrough_shader.set_uniform_2f("uvOffset", uv_offset[0], uv_offset[1]);
+        self.passthrough_shader.set_uniform_2f("uvScale", uv_scale[0], uv_scale[1]);
+
+        self.draw_fullscreen(&self.passthrough_shader);
+
+        self.passthrough_shader.unbind_program();
+        texture.unbind();
+    }
+
     /// Renders a mesh using the provided shader and vertex data onto the current bound framebuffer.
     pub fn render_mesh(&self, mesh: &Mesh) {
         unsafe {
@@ -482,6 +633,33 @@ impl GlWindow {
             mesh.unbind();
         }
     }
+
+    /// Runs the first `vertex_count` vertices of a mesh through the currently bound transform feedback shader
+    /// (see [`crate::Shader::new_transform_feedback_from_source`]) without rasterizing anything, capturing the
+    /// outputs into the vertex buffers of the destination meshes. Interleaved shaders use one destination, separate
+    /// shaders one destination per varying. The destinations need enough room, see [`Mesh::allocate_vertices`].
+    /// Swapping source and destination every frame allows for simulations on the GPU (ping-ponging).
+    pub fn capture_transform_feedback(&self, mesh: &Mesh, vertex_count: usize, destinations: &[&Mesh]) {
+        unsafe {
+            for (index, destination) in destinations.iter().enumerate() {
+                gl::BindBufferBase(gl::TRANSFORM_FEEDBACK_BUFFER, index as u32, destination.vertex_buffer);
+            }
+
+            gl::Enable(gl::RASTERIZER_DISCARD);
+            mesh.bind();
+
+            gl::BeginTransformFeedback(gl::POINTS);
+            gl::DrawArrays(gl::POINTS, 0, vertex_count as i32);
+            gl::EndTransformFeedback();
+
+            mesh.unbind();
+            gl::Disable(gl::RASTERIZER_DISCARD);
+
+            for index in 0..destinations.len() {
+                gl::BindBufferBase(gl::TRANSFORM_FEEDBACK_BUFFER, index as u32, 0);
+            }
+        }
+    }
 }
 
 /// Struct to more easily allow setting window features.
@@ -495,6 +673,10 @@ pub struct WindowConfig {
     pub target_framerate: u32,
     pub transparent_framebuffer: bool,
     pub anti_aliasing: u32,
+    /// Requests an sRGB capable default framebuffer and enables the conversion of linear shader output to sRGB.
+    pub srgb_framebuffer: bool,
+    /// The amount of bits of the stencil buffer of the default framebuffer, 0 for no stencil buffer.
+    pub stencil_bits: u32,
 }
 
 impl Default for WindowConfig {
@@ -508,6 +690,8 @@ impl Default for WindowConfig {
             target_framerate: 60,
             transparent_framebuffer: false,
             anti_aliasing: 4,
+            srgb_framebuffer: false,
+            stencil_bits: 8,
         }
     }
 }
@@ -599,6 +783,42 @@ impl From<DepthType> for Option<gl::types::GLenum> {
     }
 }
 
+/// Enum representing what happens to the value in the stencil buffer, see [`GlWindow::set_stencil_op`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StencilOp {
+    /// Keeps the current value.
+    Keep,
+    /// Sets the value to 0.
+    Zero,
+    /// Sets the value to the reference value of [`GlWindow::set_stencil_test`].
+    Replace,
+    /// Increments the value, stopping at the maximum value.
+    Increment,
+    /// Increments the value, wrapping around to 0 after the maximum value.
+    IncrementWrap,
+    /// Decrements the value, stopping at 0.
+    Decrement,
+    /// Decrements the value, wrapping around to the maximum value after 0.
+    DecrementWrap,
+    /// Inverts all bits of the value.
+    Invert,
+}
+
+impl From<StencilOp> for gl::types::GLenum {
+    fn from(op: StencilOp) -> Self {
+        match op {
+            StencilOp::Keep => gl::KEEP,
+            StencilOp::Zero => gl::ZERO,
+            StencilOp::Replace => gl::REPLACE,
+            StencilOp::Increment => gl::INCR,
+            StencilOp::IncrementWrap => gl::INCR_WRAP,
+            StencilOp::Decrement => gl::DECR,
+            StencilOp::DecrementWrap => gl::DECR_WRAP,
+            StencilOp::Invert => gl::INVERT,
+        }
+    }
+}
+
 /// Enum storing all different rendering types.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RenderingType {